@@ -1,24 +1,45 @@
 
-// max turn 30 * 50 = 1500
-const MAX_DEPTH: usize = 3000;
-//const MAX_MOVES: usize = 218;
-
 mod fen;
 mod display;
+pub mod bitboard;
+pub mod attacks;
 pub mod r#move;
+pub mod move_list;
 pub mod piece;
+pub mod piece_list;
 pub mod legal_moves;
+pub mod legality;
+pub mod history;
+pub mod magic;
 pub mod perft;
+pub mod polyglot;
+pub mod reference;
+pub mod san;
+pub mod see;
+pub mod validation;
+pub mod termination;
 pub mod zobrist;
 
 pub use fen::*;
+pub use validation::*;
+pub use termination::*;
+pub use san::*;
+pub use perft::*;
+pub use reference::*;
+pub use history::*;
 pub use piece::*;
+pub use piece_list::*;
+pub use bitboard::*;
 
 use castle::*;
 use legal_moves::*;
+pub use legal_moves::GenType;
+use magic::*;
 pub use super::*;
 pub use r#move::*;
+pub use move_list::*;
 pub use zobrist::*;
+pub use polyglot::POLYGLOT_RANDOM;
 pub use utils::*;
 
 pub type Square = i8;
@@ -29,78 +50,89 @@ pub mod castle {
     pub const CASTLE_WHITE_KING: u8 = 2;
     pub const CASTLE_BLACK_QUEEN: u8 = 4;
     pub const CASTLE_BLACK_KING: u8 = 8;
+
+    pub const KING_SIDE: usize = 0;
+    pub const QUEEN_SIDE: usize = 1;
+    // [colour index][side]
+    pub const CASTLE_RIGHTS: [[u8; 2]; 2] = [
+        [CASTLE_WHITE_KING, CASTLE_WHITE_QUEEN],
+        [CASTLE_BLACK_KING, CASTLE_BLACK_QUEEN],
+    ];
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SideState {
     pub king: Square,
-    pub pawns: Vec<Square>,
-    pub knights: Vec<Square>,
-    pub bishops: Vec<Square>,
-    pub rooks: Vec<Square>,
-    pub queens: Vec<Square>,
+    pub pawns: PieceList,
+    pub knights: PieceList,
+    pub bishops: PieceList,
+    pub rooks: PieceList,
+    pub queens: PieceList,
+    // where the piece on each square is in its piece list
+    pub list_index: [u8; 64],
+    pub bitboards: [Bitboard; 6],
+    pub occupancy: Bitboard,
     pub attacks: [i8; 64],
     pub piece_attacks: [[i8; 64]; 6],
 }
 impl Default for SideState {
     fn default() -> Self {
-        let mut side = Self { king: Default::default(), pawns: Default::default(), knights: Default::default(), bishops: Default::default(), rooks: Default::default(), queens: Default::default(), attacks: [0; 64], piece_attacks: [[0; 64]; 6] };
-        side.pawns.reserve_exact(8);
-        side.knights.reserve_exact(10);
-        side.bishops.reserve_exact(10);
-        side.rooks.reserve_exact(10);
-        side.queens.reserve_exact(10);
-        return side
+        Self { king: Default::default(), pawns: PieceList::new(), knights: PieceList::new(), bishops: PieceList::new(), rooks: PieceList::new(), queens: PieceList::new(), list_index: [0; 64], bitboards: [EMPTY; 6], occupancy: EMPTY, attacks: [0; 64], piece_attacks: [[0; 64]; 6] }
     }
 }
-impl Chess {
-    fn pieces(&mut self, colour_index: usize, piece_type: PieceType) -> &mut Vec<Square> {
+impl SideState {
+    pub fn pieces(&self, piece_type: PieceType) -> &PieceList {
         match piece_type {
-            KING => unreachable!(),
-            PAWN => &mut self.side[colour_index].pawns,
-            KNIGHT => &mut self.side[colour_index].knights,
-            BISHOP => &mut self.side[colour_index].bishops,
-            ROOK => &mut self.side[colour_index].rooks,
-            QUEEN => &mut self.side[colour_index].queens,
+            PAWN => &self.pawns,
+            KNIGHT => &self.knights,
+            BISHOP => &self.bishops,
+            ROOK => &self.rooks,
+            QUEEN => &self.queens,
             _ => unreachable!(),
         }
     }
-    
+}
+impl Chess {
     fn new_piece(&mut self, colour_index: usize, piece_type: PieceType, square: Square) {
         match piece_type {
             KING => self.side[colour_index].king = square,
-            _ => self.pieces(colour_index, piece_type).push(square),
+            _ => {
+                self.side[colour_index].add_to_list(piece_type, square);
+                self.add_material(piece_type, colour_index);
+            }
         }
+        self.side[colour_index].toggle_bitboard(piece_type, square);
         self.piece_hash(piece_type, colour_index, square);
     }
 
     fn add_piece(&mut self, colour_index: usize, piece_type: PieceType, square: Square) {
-        self.pieces(colour_index, piece_type).push(square);
+        self.side[colour_index].add_to_list(piece_type, square);
+        self.add_material(piece_type, colour_index);
+        self.side[colour_index].toggle_bitboard(piece_type, square);
         self.piece_hash(piece_type, colour_index, square);
     }
     
     fn remove_piece(&mut self, colour_index: usize, piece_type: PieceType, square: Square) {
-        let pieces = self.pieces(colour_index, piece_type);
-        pieces.swap_remove(pieces.iter().position(|&p|p == square).unwrap());
+        self.side[colour_index].remove_from_list(piece_type, square);
+        self.remove_material(piece_type, colour_index);
+        self.side[colour_index].toggle_bitboard(piece_type, square);
         self.piece_hash(piece_type, colour_index, square);
     }
     
     fn move_piece(&mut self, colour_index: usize, piece_type: PieceType, start: Square, target: Square) {
         match piece_type {
             KING => self.side[colour_index].king = target,
-            _ => {
-                let pieces = self.pieces(colour_index, piece_type);
-                let index = pieces.iter().position(|&p|p == start).unwrap();
-                pieces[index] = target;
-            }
+            _ => self.side[colour_index].move_in_list(piece_type, start, target),
         }
+        self.side[colour_index].toggle_bitboard(piece_type, start);
+        self.side[colour_index].toggle_bitboard(piece_type, target);
         self.piece_hash(piece_type, colour_index, start);
         self.piece_hash(piece_type, colour_index, target);
     }
 
     fn spread_attack_direcion(&mut self, piece_type: PieceType, colour: Colour, start: Square, dir_index: usize, value: i8) {
         let dir = DIRECTION_OFFSETS[dir_index];
-        let dist_edge = unsafe {NUM_SQUARES_TO_EDGES[start as usize][dir_index]};
+        let dist_edge = NUM_SQUARES_TO_EDGES[start as usize][dir_index];
         let piece_index = piece_type.piece_index();
         let colour_index = colour.colour_index();
         let opponent_king = self.side[colour.opponent().colour_index()].king;
@@ -116,6 +148,7 @@ impl Chess {
         }
     }
     fn update_attack_pieces(&mut self, piece_type: PieceType, colour: Colour, square: Square, value: i8) {
+        if !self.attack_maps {return}
         let mut white_control = self.side[WHITE.colour_index()].attacks[square as usize] > 0;
         let mut black_control = self.side[BLACK.colour_index()].attacks[square as usize] > 0;
         white_control &= Piece::new(piece_type, colour) != Piece::new(KING, BLACK);
@@ -126,26 +159,31 @@ impl Chess {
         for dir_index in 0..8 {
 
             let dir = DIRECTION_OFFSETS[dir_index];
-            let dist_edge = unsafe {NUM_SQUARES_TO_EDGES[square as usize][dir_index]};
+            let dist_edge = NUM_SQUARES_TO_EDGES[square as usize][dir_index];
             
+            let (mut look_white, mut look_black) = (white_control, black_control);
             for n in 0..dist_edge {
                 let target = square as i8 + ((n+1) as i8*dir);
                 let target_piece = self.board[target as usize];
                 if target_piece == NONE {continue}
                 
                 let is_target_white = target_piece.is_colour(WHITE);
-                if (white_control && is_target_white)
-                || (black_control && !is_target_white) {
+                if (look_white && is_target_white)
+                || (look_black && !is_target_white) {
                     if target_piece.get_type().is_sliding()
                     && target_piece.get_type().get_sliding_indices().contains(&dir_index) {
                         self.spread_attack_direcion(target_piece.get_type(), Colour::new(is_target_white), square, DIRECTION_OPPOST_INDEX[dir_index], value);
                     }
                 }
-                break;
+                // the sliders see through the enemy king
+                look_white &= target_piece == Piece::new(KING, BLACK);
+                look_black &= target_piece == Piece::new(KING, WHITE);
+                if !(look_white || look_black) {break}
             }
         }
     }
     fn piece_attack(&mut self, piece_type: PieceType, colour: Colour, square: Square, value: i8) {
+        if !self.attack_maps {return}
         if piece_type.is_sliding() {
             for dir_index in piece_type.get_sliding_indices() {
                 self.spread_attack_direcion(piece_type, colour, square, dir_index, value);
@@ -155,7 +193,7 @@ impl Chess {
             let colour_index = colour.colour_index();
             let piece_index = piece_type.piece_index();
             
-            for &mut target in piece_attacks(piece_type, colour, square) {
+            for &target in piece_attacks(piece_type, colour, square) {
                 self.side[colour_index].piece_attacks[piece_index][target as usize] += value;
                 self.side[colour_index].attacks[target as usize] += value;
             }
@@ -169,6 +207,28 @@ impl Chess {
         self.piece_attack(piece_type, colour, square, -1);
         self.update_attack_pieces(piece_type, colour, square, 1);
     }
+
+    pub fn has_attack_maps(&self) -> bool { self.attack_maps }
+    // without the attack maps make_move and unmake_move leave them empty and the move generation
+    // asks the bitboards instead, turning them back on computes them from scratch
+    pub fn set_attack_maps(&mut self, attack_maps: bool) {
+        for side in &mut self.side {
+            side.attacks = [0; 64];
+            side.piece_attacks = [[0; 64]; 6];
+        }
+        self.attack_maps = attack_maps;
+        for square in 0..64 {
+            let piece = self.board(square);
+            if piece == NONE {continue}
+            self.piece_attack(piece.get_type(), piece.get_colour(), square, 1);
+        }
+    }
+    // a copy for perft and the like, where only the legal moves matter
+    pub fn without_attack_maps(&self) -> Chess {
+        let mut chess = self.clone();
+        chess.set_attack_maps(false);
+        chess
+    }
 }
 
 /*
@@ -188,39 +248,43 @@ pub struct Chess {
     board: [Piece; 64],
     pub en_passant: Square,
     pub castling: u8,
+    // starting squares of the castling rooks, [colour index][side]
+    castling_rooks: [[Square; 2]; 2],
     pub half_move: u16,
     pub full_turn: u16,
     is_white_to_move: bool,
     pub side: [SideState; 2],
-    // eaten piece | en passant | castlilg | half move clock | hash
-    pub irreversable_state: Vec<(PieceType, Square, u8, u16, Hash)>,
+    pub history: Vec<StateInfo>,
+    moves: Vec<Move>,
     hash: Hash,
+    pawn_key: Hash,
+    material_key: Hash,
+    keys: &'static ZobristKeys,
+    attack_maps: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChessOutcome {Draw, WhiteWinner, BlackWinner}
 
 impl Chess {
     pub fn new() -> Chess {
-        legal_moves::precompute();
-        
-        let mut chess = Chess {
+        Chess {
             board: [NONE; 64],
             en_passant: -1,
             castling: CASTLE_NONE,
+            castling_rooks: [[7, 0], [63, 56]],
             half_move: 0,
             full_turn: 1,
             is_white_to_move: true,
             side: [Default::default(), Default::default()],
-            irreversable_state: Vec::new(),
-            //moves_history: Vec::new(),
+            history: Vec::new(),
+            moves: Vec::new(),
             hash: 0,
-        };
-        chess.new_piece(0, KING, 15);
-        chess.new_piece(1, KING, 49);
-        chess.irreversable_state.reserve_exact(MAX_DEPTH);
-        //chess.moves_history.reserve_exact(MAX_MOVES);
-        chess
+            pawn_key: 0,
+            material_key: 0,
+            keys: &STANDARD_KEYS,
+            attack_maps: true,
+        }
     }
     pub fn start_position() -> Chess {Chess::build(START_POSITION)}
     pub fn position(num: usize) -> Chess {
@@ -247,29 +311,27 @@ impl Chess {
     fn get_repetitions(&self) -> u16 {
         let hash = self.hash();
         let positions = self.half_move;
-        self.irreversable_state.iter().rev().take(positions as usize).skip(3).step_by(2).take_while(|el|{
-            self.castling == el.2
-        }).filter(|el|el.4 == hash).count() as u16
+        self.history.iter().rev().take(positions as usize).skip(3).step_by(2).take_while(|state|{
+            self.castling == state.castling
+        }).filter(|state|state.hash == hash).count() as u16
     }
     pub fn board(&self, square: Square) -> Piece { self.board[square as usize] }
     pub fn colour_to_move(&self) -> Colour { Colour::new(self.is_white_to_move) }
     pub fn colour_index(&self) -> usize { !self.is_white_to_move as usize /*self.colour_to_move().colour_index()*/ }
     pub fn opponent_index(&self) -> usize { self.is_white_to_move as usize /*self.colour_to_move().opponent().colour_index()*/ }
     fn get_king_square(&self) -> Square { self.side[self.colour_index()].king }
-    fn get_king_treats(&self) -> i8 { self.side[self.opponent_index()].attacks[self.get_king_square() as usize] }
-    fn is_king_in_check(&self) -> bool { self.get_king_treats() != 0 }
-    pub fn is_finished(&self, moves: &Vec<Move>) -> bool {
-        moves.len() == 0 || self.get_repetitions() >= 1 || self.half_move >= 100
+    fn get_king_treats(&self) -> i8 {
+        if !self.attack_maps {return self.checkers().count_ones() as i8}
+        self.side[self.opponent_index()].attacks[self.get_king_square() as usize]
     }
-    pub fn is_finished_for_real(&self, moves: &Vec<Move>) -> bool {
-        moves.len() == 0 || self.get_repetitions() >= 3 || self.half_move >= 100
-    }
-    pub fn get_outcome(&self, moves: &Vec<Move>) -> ChessOutcome {
-        if self.is_king_in_check() && moves.len() == 0 {
-            if self.is_white_to_move {return ChessOutcome::BlackWinner}
-            return ChessOutcome::WhiteWinner;
+    pub fn in_check(&self) -> bool { self.get_king_treats() != 0 }
+    // castling is encoded as the king moving, so it never counts even onto its own rook
+    pub fn is_capture(&self, r#move: Move) -> bool {
+        match r#move.flag() {
+            EN_PASSANT_FLAG => true,
+            CASTLE_FLAG => false,
+            _ => self.board(r#move.target()) != NONE,
         }
-        return ChessOutcome::Draw;
     }
 
     pub fn make_move(&mut self, r#move: Move) {
@@ -280,31 +342,19 @@ impl Chess {
 
         match r#move.flag() {
             CASTLE_FLAG => {
-                self.irreversable_state.push((NONE_TYPE, self.en_passant, self.castling, self.half_move, self.hash()));
+                self.push_state(NONE_TYPE, r#move);
                 self.en_passant = -1;
                 self.half_move += 1;
-                if self.is_white_to_move {
-                    self.castling &= !(CASTLE_WHITE_QUEEN | CASTLE_WHITE_KING);
-                    if r#move.is_king_castling() {
-                        self.make_castle(WHITE, 4, 7, 6, 5);
-                    } else {
-                        self.make_castle(WHITE, 4, 0, 2, 3);
-                    }
-                } else {
-                    self.castling &= !(CASTLE_BLACK_QUEEN | CASTLE_BLACK_KING);
-                    if r#move.is_king_castling() {
-                        self.make_castle(BLACK, 60, 63, 62, 61);
-                    } else {
-                        self.make_castle(BLACK, 60, 56, 58, 59);
-                    }
-                }
+                self.castling &= !(CASTLE_RIGHTS[colour_index][KING_SIDE] | CASTLE_RIGHTS[colour_index][QUEEN_SIDE]);
+                let (king, rook, king_target, rook_target) = self.castle_squares(colour_index, r#move);
+                self.make_castle(colour, king, rook, king_target, rook_target);
             }
             NO_FLAG => {
                 let (start, target) = (r#move.start(), r#move.target());
                 let start_type = self.board[start as usize].get_type();
                 let target_type = self.board[target as usize].get_type();
                 
-                self.irreversable_state.push((target_type, self.en_passant, self.castling, self.half_move, self.hash()));
+                self.push_state(target_type, r#move);
                 self.en_passant = -1;
                 self.update_castling(start, target);
                 
@@ -331,7 +381,7 @@ impl Chess {
             }
             DOUBLE_PUSH_FLAG => {
                 let (start, target) = (r#move.start(), r#move.target());
-                self.irreversable_state.push((NONE_TYPE, self.en_passant, self.castling, self.half_move, self.hash()));
+                self.push_state(NONE_TYPE, r#move);
                 self.en_passant = if self.is_white_to_move {target - 8} else {target + 8};
                 self.half_move = 0;
                 
@@ -346,7 +396,7 @@ impl Chess {
                 let (start, target) = (r#move.start(), r#move.target());
                 let eaten_square = if self.is_white_to_move {target - 8} else {target + 8};
 
-                self.irreversable_state.push((PAWN, self.en_passant, self.castling, self.half_move, self.hash()));
+                self.push_state(PAWN, r#move);
                 self.en_passant = -1;
                 self.half_move = 0;
 
@@ -365,7 +415,7 @@ impl Chess {
                 let promotion_type = r#move.promotion_type();
                 let target_type = self.board[target as usize].get_type();
 
-                self.irreversable_state.push((target_type, self.en_passant, self.castling, self.half_move, self.hash()));
+                self.push_state(target_type, r#move);
                 self.en_passant = -1;
                 self.half_move = 0;
                 
@@ -380,7 +430,7 @@ impl Chess {
                 if target_type == NONE_TYPE {
                     self.put_attack_and_update(promotion_type, colour, target);
                 } else {
-                    self.update_castling_pawn_eats_and_promotion(target);
+                    self.update_castling(target, target);
                     self.remove_piece(opponent_index, target_type, target);
 
                     self.piece_attack(target_type, colour.opponent(), target, -1);
@@ -390,75 +440,69 @@ impl Chess {
         }
         
         self.black_turn_hash();
-        self.full_turn += self.is_white_to_move as u16; // false == 1
+        self.full_turn += !self.is_white_to_move as u16; // false == 1
         self.is_white_to_move = !self.is_white_to_move;
+        #[cfg(feature = "verify-hash")]
+        self.verify_keys();
     }
 
+    // king, rook, king target and rook target of a castling move
+    fn castle_squares(&self, colour_index: usize, r#move: Move) -> (Square, Square, Square, Square) {
+        let (king, king_target) = (r#move.start(), r#move.target());
+        if r#move.is_king_castling() {
+            (king, self.castling_rooks[colour_index][KING_SIDE], king_target, king_target - 1)
+        } else {
+            (king, self.castling_rooks[colour_index][QUEEN_SIDE], king_target, king_target + 1)
+        }
+    }
+    pub fn castling_rook(&self, colour: Colour, side: usize) -> Square {
+        self.castling_rooks[colour.colour_index()][side]
+    }
+
+    // in Chess960 the king or the rook may already stand on a target square
     fn make_castle(&mut self, colour: Colour, king: Square, rook: Square, king_target: Square, rook_target: Square) {
         self.move_piece(colour.colour_index(), KING, king, king_target);
         self.move_piece(colour.colour_index(), ROOK, rook, rook_target);
         
+        self.board[king as usize] = NONE;
+        self.remove_attack_and_update(KING, colour, king);
         self.board[rook as usize] = NONE;
         self.remove_attack_and_update(ROOK, colour, rook);
         self.board[king_target as usize] = Piece::new(KING, colour);
         self.put_attack_and_update(KING, colour, king_target);
-        self.board[king as usize] = NONE;
-        self.remove_attack_and_update(KING, colour, king);
         self.board[rook_target as usize] = Piece::new(ROOK, colour);
         self.put_attack_and_update(ROOK, colour, rook_target);
     }
 
     fn update_castling(&mut self, start: Square, target: Square) {
-        for square in [start, target] {
-            match square {
-                0 => self.castling &= !CASTLE_WHITE_QUEEN,
-                7 => self.castling &= !CASTLE_WHITE_KING,
-                4 => self.castling &= !(CASTLE_WHITE_QUEEN + CASTLE_WHITE_KING),
-                56 => self.castling &= !CASTLE_BLACK_QUEEN,
-                63 => self.castling &= !CASTLE_BLACK_KING,
-                60 => self.castling &= !(CASTLE_BLACK_QUEEN + CASTLE_BLACK_KING),
-                _ => (),
+        for (colour_index, rights) in CASTLE_RIGHTS.into_iter().enumerate() {
+            if self.castling & (rights[KING_SIDE] | rights[QUEEN_SIDE]) == 0 {continue}
+            if start == self.side[colour_index].king {
+                self.castling &= !(rights[KING_SIDE] | rights[QUEEN_SIDE]);
+            }
+            for (right, rook) in rights.into_iter().zip(self.castling_rooks[colour_index]) {
+                if start == rook || target == rook {
+                    self.castling &= !right;
+                }
             }
-        }
-    }
-    fn update_castling_pawn_eats_and_promotion(&mut self, target: Square) {
-        match target {
-            0 => self.castling &= !CASTLE_WHITE_QUEEN,
-            7 => self.castling &= !CASTLE_WHITE_KING,
-            56 => self.castling &= !CASTLE_BLACK_QUEEN,
-            63 => self.castling &= !CASTLE_BLACK_KING,
-            _ => (),
         }
     }
 
     pub fn unmake_move(&mut self, r#move: Move) {
         let (start, target) = (r#move.start(), r#move.target());
         self.is_white_to_move = !self.is_white_to_move;
-        self.full_turn -= self.is_white_to_move as u16; // false == 1
         
         let colour = self.colour_to_move();
         let colour_index = self.colour_index();
         let opponent_index = self.opponent_index();
-        let target_type;
         self.black_turn_hash();
         
-        (target_type, self.en_passant, self.castling, self.half_move, _) = self.irreversable_state.pop().unwrap();
+        let target_type = self.pop_state().captured;
         
         match r#move.flag() {
             CASTLE_FLAG => {
-                if self.is_white_to_move {
-                    if r#move.is_king_castling() {
-                        self.make_castle(WHITE, 6, 5, 4, 7);
-                    } else {
-                        self.make_castle(WHITE, 2, 3, 4, 0);
-                    }
-                } else {
-                    if r#move.is_king_castling() {
-                        self.make_castle(BLACK, 62, 61, 60, 63);
-                    } else {
-                        self.make_castle(BLACK, 58, 59, 60, 56);
-                    }
-                }
+                let (king, rook, king_target, rook_target) = self.castle_squares(colour_index, r#move);
+                self.make_castle(colour, king_target, rook_target, king, rook);
             }
             NO_FLAG => {
                 let start_type = self.board[target as usize].get_type();
@@ -519,8 +563,33 @@ impl Chess {
                 }
             }
         }
+        #[cfg(feature = "verify-hash")]
+        self.verify_keys();
     }
-} 
+
+    // passes the turn, never while in check; the board and the attack maps stay as they are
+    pub fn make_null_move(&mut self) {
+        debug_assert!(!self.in_check(), "null move while in check");
+        self.push_state(NONE_TYPE, NONE_MOVE);
+        self.en_passant = -1;
+        // repetitions are not looked for across a null move
+        self.half_move = 0;
+
+        self.black_turn_hash();
+        self.full_turn += !self.is_white_to_move as u16; // false == 1
+        self.is_white_to_move = !self.is_white_to_move;
+        #[cfg(feature = "verify-hash")]
+        self.verify_keys();
+    }
+
+    pub fn unmake_null_move(&mut self) {
+        self.is_white_to_move = !self.is_white_to_move;
+        self.black_turn_hash();
+        self.pop_state();
+        #[cfg(feature = "verify-hash")]
+        self.verify_keys();
+    }
+}
 
 pub mod utils {
     use super::*;
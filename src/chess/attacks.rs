@@ -0,0 +1,77 @@
+use super::*;
+
+// answers computed from the bitboards, so they don't depend on the incremental attack maps
+impl Chess {
+    // pieces of both colours attacking the square, sliders looking through the given occupancy
+    pub(crate) fn attackers_to_occupied(&self, square: Square, occupied: Bitboard) -> Bitboard {
+        let pieces = |piece_type| self.pieces_bb(WHITE, piece_type) | self.pieces_bb(BLACK, piece_type);
+        let bitboard = |list: &[Square]| list.iter().fold(EMPTY, |bb, &square| bb | square_bb(square));
+        let queens = pieces(QUEEN);
+        // a pawn of one colour is attacked from the squares a pawn of the other colour would attack
+        bitboard(&PAWN_ATTACKS[WHITE.colour_index()][square as usize]) & self.pieces_bb(BLACK, PAWN)
+        | bitboard(&PAWN_ATTACKS[BLACK.colour_index()][square as usize]) & self.pieces_bb(WHITE, PAWN)
+        | bitboard(&KNIGHT_ATTACKS[square as usize]) & pieces(KNIGHT)
+        | bitboard(&KING_ATTACKS[square as usize]) & pieces(KING)
+        | rook_attacks(square, occupied) & (pieces(ROOK) | queens)
+        | bishop_attacks(square, occupied) & (pieces(BISHOP) | queens)
+    }
+
+    // pieces of the colour attacking the square in the current position
+    pub fn attackers_to(&self, square: Square, colour: Colour) -> Bitboard {
+        self.attackers_to_occupied(square, self.occupied_bb()) & self.colour_bb(colour)
+    }
+
+    pub fn is_square_attacked(&self, square: Square, colour: Colour) -> bool {
+        self.attackers_to(square, colour) != EMPTY
+    }
+
+    // the opponent pieces giving check to the side to move
+    pub fn checkers(&self) -> Bitboard {
+        self.attackers_to(self.side[self.colour_index()].king, self.colour_to_move().opponent())
+    }
+
+    // pieces of the colour that can't leave the line between their king and an enemy slider
+    pub fn pinned_pieces(&self, colour: Colour) -> Bitboard {
+        let (pins, _) = self.get_pins_and_slide_attack(self.side[colour.colour_index()].king, colour);
+        pins.pinned
+    }
+}
+
+#[cfg(test)]
+mod attacks_tests {
+    use super::*;
+
+    #[test]
+    fn checkers_and_pins() {
+        let chess = Chess::build("4k3/8/8/1b6/8/3P4/4K2r/8 w - - 0 1");
+        assert!(chess.in_check());
+        assert_eq!(chess.checkers(), square_bb(15));
+        assert_eq!(chess.pinned_pieces(WHITE), square_bb(19));
+        assert_eq!(chess.pinned_pieces(BLACK), EMPTY);
+        assert!(chess.is_square_attacked(7, BLACK));
+        assert!(!chess.is_square_attacked(7, WHITE));
+        assert_eq!(chess.attackers_to(11, WHITE), square_bb(12));
+        // the king blocks the rook, unlike in the attack maps
+        assert_eq!(chess.attackers_to(11, BLACK), EMPTY);
+        assert!(!chess.gives_check(Move::from_text(&chess, "e2d1")));
+
+        let chess = Chess::start_position();
+        assert!(!chess.in_check());
+        assert_eq!(chess.checkers(), EMPTY);
+        assert_eq!(chess.attackers_to(21, WHITE), square_bb(6) | square_bb(12) | square_bb(14));
+    }
+
+    #[test]
+    fn agrees_with_attack_maps() {
+        for num in 1..=6 {
+            let chess = Chess::position(num);
+            assert_eq!(chess.in_check(), chess.checkers() != EMPTY);
+            for square in 0..64 {
+                for colour in [WHITE, BLACK] {
+                    let attacked = chess.side[colour.colour_index()].attacks[square as usize] != 0;
+                    assert_eq!(chess.is_square_attacked(square, colour), attacked, "{num} {}", square_to_text(square));
+                }
+            }
+        }
+    }
+}
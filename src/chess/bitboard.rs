@@ -0,0 +1,90 @@
+use super::*;
+
+pub type Bitboard = u64;
+
+pub const EMPTY: Bitboard = 0;
+pub const FILE_A: Bitboard = 0x0101010101010101;
+pub const FILE_H: Bitboard = FILE_A << 7;
+pub const RANK_1: Bitboard = 0xff;
+pub const RANK_8: Bitboard = RANK_1 << 56;
+
+pub fn square_bb(square: Square) -> Bitboard { 1 << square }
+pub fn has_square(bitboard: Bitboard, square: Square) -> bool { bitboard & square_bb(square) != 0 }
+pub fn lsb(bitboard: Bitboard) -> Square { bitboard.trailing_zeros() as Square }
+pub fn pop_lsb(bitboard: &mut Bitboard) -> Square {
+    let square = lsb(*bitboard);
+    *bitboard &= *bitboard - 1;
+    square
+}
+
+// iterate the squares of a bitboard from a1 to h8
+pub struct Squares(Bitboard);
+impl Iterator for Squares {
+    type Item = Square;
+    fn next(&mut self) -> Option<Square> {
+        if self.0 == EMPTY {return None}
+        Some(pop_lsb(&mut self.0))
+    }
+}
+pub fn squares(bitboard: Bitboard) -> Squares { Squares(bitboard) }
+
+impl SideState {
+    pub(super) fn toggle_bitboard(&mut self, piece_type: PieceType, square: Square) {
+        self.bitboards[piece_type.piece_index()] ^= square_bb(square);
+        self.occupancy ^= square_bb(square);
+    }
+}
+
+impl Chess {
+    pub fn pieces_bb(&self, colour: Colour, piece_type: PieceType) -> Bitboard {
+        self.side[colour.colour_index()].bitboards[piece_type.piece_index()]
+    }
+    pub fn colour_bb(&self, colour: Colour) -> Bitboard { self.side[colour.colour_index()].occupancy }
+    pub fn occupied_bb(&self) -> Bitboard { self.side[0].occupancy | self.side[1].occupancy }
+
+    pub fn display_bitboard(bitboard: Bitboard) {
+        println!(r#"  /========================\"#);
+        for j in (0..8).rev() {
+            print!("{j} |");
+            for i in 0..8 {
+                let square = i+j*8;
+                let look = if has_square(bitboard, square) {'x'} else if is_black_square(square) {':'} else {' '};
+                if is_black_square(square) {
+                    print!(":{look}:");
+                } else {
+                    print!(" {look} ");
+                }
+            }
+            println!("|");
+        }
+        println!(r#"  \========================/"#);
+    }
+}
+
+#[cfg(test)]
+mod bitboard_tests {
+    use super::*;
+
+    fn assert_bitboards_match_board(chess: &Chess) {
+        for square in 0..64 {
+            let piece = chess.board(square);
+            for colour in [WHITE, BLACK] {
+                assert_eq!(has_square(chess.colour_bb(colour), square), piece != NONE && piece.is_colour(colour));
+                for piece_type in [KING, KNIGHT, PAWN, BISHOP, ROOK, QUEEN] {
+                    assert_eq!(has_square(chess.pieces_bb(colour, piece_type), square), piece == Piece::new(piece_type, colour));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn squares_iterator() {
+        assert_eq!(squares(square_bb(3) | square_bb(40) | square_bb(63)).collect::<Vec<_>>(), vec![3, 40, 63]);
+        assert_eq!(squares(EMPTY).count(), 0);
+    }
+
+    #[test]
+    fn bitboards_follow_moves() {
+        walk_test_positions(3, |_| (), |chess| assert_bitboards_match_board(chess));
+    }
+}
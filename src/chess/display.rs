@@ -5,7 +5,7 @@ use super::*;
 
 impl Chess {
     pub fn display(&self) {
-        if unsafe {!DISPLAY} {return}
+        if !DISPLAY.load(std::sync::atomic::Ordering::Relaxed) {return}
         print!(r#"
   /========================\
 8 |   :::   :::   :::   :::|
@@ -33,7 +33,7 @@ impl Chess {
 
 
     pub fn update_display(&self, r#move: Move) {
-        if unsafe {!DISPLAY} {return}
+        if !DISPLAY.load(std::sync::atomic::Ordering::Relaxed) {return}
         let (start, target) = (r#move.start(), r#move.target());
         self.update_square(start);
         self.update_square(target);
@@ -45,9 +45,9 @@ impl Chess {
                 {self.update_square(target+8)}
             }
             CASTLE_FLAG => {
-                let range = if r#move.is_king_castling()
-                {start..start+4} else {start-4..start+1};
-                for square in range {
+                // in Chess960 king and rook can come from anywhere on the back rank
+                let back_rank = start - start % 8;
+                for square in back_rank..back_rank+8 {
                     self.update_square(square);
                 }
             }
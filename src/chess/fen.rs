@@ -2,61 +2,314 @@ use super::*;
 
 pub const START_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    MissingField(&'static str),
+    BadPlacement(String),
+    RankTooLong(usize),
+    BadSide(String),
+    BadCastling(String),
+    BadEnPassant(String),
+    BadClock(String),
+    TooManyPieces(char),
+    IllegalPosition(Vec<PositionError>),
+}
+
+impl std::fmt::Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "missing {field}"),
+            FenError::BadPlacement(placement) => write!(f, "bad piece placement \"{placement}\""),
+            FenError::RankTooLong(rank) => write!(f, "too many squares on rank {rank}"),
+            FenError::BadSide(side) => write!(f, "bad side to move \"{side}\""),
+            FenError::BadCastling(castling) => write!(f, "bad castling ability \"{castling}\""),
+            FenError::BadEnPassant(square) => write!(f, "bad en passant square \"{square}\""),
+            FenError::BadClock(clock) => write!(f, "bad move clock \"{clock}\""),
+            FenError::TooManyPieces(symbol) => write!(f, "too many '{symbol}' pieces"),
+            FenError::IllegalPosition(errors) => {
+                write!(f, "illegal position: ")?;
+                for (index, error) in errors.iter().enumerate() {
+                    if index > 0 {write!(f, ", ")?}
+                    write!(f, "{error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+impl std::error::Error for FenError {}
+
+// Scharnagl numbering of the Chess960 start positions, 518 is the standard one
+pub fn chess960_fen(index: u16) -> String {
+    assert!(index < 960, "Chess960 positions go from 0 to 959");
+    fn place(rank: &mut [Option<char>; 8], nth_empty: usize, symbol: char) {
+        let file = (0..8).filter(|&file| rank[file].is_none()).nth(nth_empty).unwrap();
+        rank[file] = Some(symbol);
+    }
+    let mut rank = [None; 8];
+    let mut index = index as usize;
+    rank[index % 4 * 2 + 1] = Some('b');
+    index /= 4;
+    rank[index % 4 * 2] = Some('b');
+    index /= 4;
+    place(&mut rank, index % 6, 'q');
+    index /= 6;
+    let (first, second) = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)][index];
+    place(&mut rank, second, 'n');
+    place(&mut rank, first, 'n');
+    for symbol in ['r', 'k', 'r'] {
+        place(&mut rank, 0, symbol);
+    }
+    let black: String = rank.iter().map(|symbol| symbol.unwrap()).collect();
+    format!("{black}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1", black.to_ascii_uppercase())
+}
+
 impl Chess {
+    pub fn chess960(index: u16) -> Chess {Chess::build(&chess960_fen(index))}
+
     pub fn build(fen: &str) -> Chess {
-        let mut iter = fen.trim().split(" ");
+        Chess::from_fen(fen).unwrap_or_else(|error| panic!("invalid FEN \"{fen}\": {error}"))
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Chess, FenError> {
+        let mut iter = fen.split_whitespace();
         
         let mut chess = Chess::new();
         // Piece Placement
-        let fen_board = iter.next().expect("FEN board");
-        let (mut rank, mut file) = (7, 0);
-        for symbol in fen_board.chars() {
-            if symbol == '/' {
-                rank -= 1;
-                file = 0;
-            } else if symbol.is_ascii_digit() {
-                file += symbol.to_digit(10).unwrap() as i8;
-            } else {
-                let square = rank * 8 + file;
-                let piece = Piece::from_symbol(symbol);
-                let colour = piece.get_colour();
-
-                chess.new_piece(colour.colour_index(), piece.get_type(), square);
-                chess.put_attack_and_update(piece.get_type(), colour, square);
-                chess.board[square as usize] = piece;
-                file += 1
+        let fen_board = iter.next().ok_or(FenError::MissingField("piece placement"))?;
+        let bad_placement = || FenError::BadPlacement(fen_board.to_string());
+        let ranks: Vec<&str> = fen_board.split('/').collect();
+        if ranks.len() != 8 {return Err(bad_placement())}
+        let mut pieces = Vec::with_capacity(32);
+        for (rank, fen_rank) in (0..8).rev().zip(ranks) {
+            let mut file = 0;
+            for symbol in fen_rank.chars() {
+                if let Some(empty) = symbol.to_digit(10).filter(|empty| (1..=8).contains(empty)) {
+                    file += empty as i8;
+                } else {
+                    PieceType::try_from_symbol(symbol).ok_or_else(bad_placement)?;
+                    if file >= 8 {return Err(FenError::RankTooLong(rank as usize + 1))}
+                    pieces.push((Piece::from_symbol(symbol), rank * 8 + file));
+                    file += 1
+                }
+                if file > 8 {return Err(FenError::RankTooLong(rank as usize + 1))}
             }
+            if file != 8 {return Err(bad_placement())}
+        }
+        // every piece beyond the starting set is a promoted pawn, so a side has at most 15 pieces
+        // besides its king; that keeps the piece lists and the move lists from overflowing
+        for colour in [WHITE, BLACK] {
+            let count = |piece_type| pieces.iter().filter(|(piece, _)| *piece == Piece::new(piece_type, colour)).count();
+            let mut promoted = count(PAWN);
+            for (piece_type, start_count) in [(PAWN, 8), (KNIGHT, 2), (BISHOP, 2), (ROOK, 2), (QUEEN, 1)] {
+                if piece_type != PAWN {promoted += count(piece_type).saturating_sub(start_count)}
+                if promoted > 8 {
+                    return Err(FenError::TooManyPieces(Piece::new(piece_type, colour).symbol()))
+                }
+            }
+        }
+        // kings first, so the attacks of the other pieces already see through them
+        pieces.sort_by_key(|(piece, _)| !piece.is_type(KING));
+        for (piece, square) in pieces {
+            let (piece_type, colour) = (piece.get_type(), piece.get_colour());
+            chess.new_piece(colour.colour_index(), piece_type, square);
+            chess.put_attack_and_update(piece_type, colour, square);
+            chess.board[square as usize] = piece;
         }
         // Side to move
-        chess.set_turn(iter.next() == Some("w"));
+        match iter.next().ok_or(FenError::MissingField("side to move"))? {
+            "w" => chess.set_turn(true),
+            "b" => chess.set_turn(false),
+            side => return Err(FenError::BadSide(side.to_string())),
+        }
         // Castling ability
-        let string = iter.next();
-        if string != Some("-") {
-            for ch in string.expect("castling ability").chars() {
-                match ch {
-                    'K' => chess.castling |= CASTLE_WHITE_KING,
-                    'Q' => chess.castling |= CASTLE_WHITE_QUEEN,
-                    'k' => chess.castling |= CASTLE_BLACK_KING,
-                    'q' => chess.castling |= CASTLE_BLACK_QUEEN,
-                    _ => panic!("castling ability")
-                }
+        let string = iter.next().ok_or(FenError::MissingField("castling ability"))?;
+        if string != "-" {
+            for ch in string.chars() {
+                let colour = Colour::new(ch.is_ascii_uppercase());
+                let colour_index = colour.colour_index();
+                let back_rank = if colour == WHITE {0} else {56};
+                let king = chess.side[colour_index].king;
+                let king_file = if king / 8 == back_rank / 8 {king % 8} else {4};
+                let is_rook = |square: &Square| chess.board(*square) == Piece::new(ROOK, colour);
+                let (side, rook) = match ch.to_ascii_lowercase() {
+                    // X-FEN: the outermost rook on that side of the king
+                    'k' => (KING_SIDE, (back_rank + king_file + 1..back_rank + 8).rev().find(is_rook).unwrap_or(back_rank + 7)),
+                    'q' => (QUEEN_SIDE, (back_rank..back_rank + king_file).find(is_rook).unwrap_or(back_rank)),
+                    // Shredder-FEN: the file of the rook
+                    file @ 'a'..='h' => {
+                        let file = file as Square - 'a' as Square;
+                        if file == king_file {return Err(FenError::BadCastling(string.to_string()))}
+                        (if file > king_file {KING_SIDE} else {QUEEN_SIDE}, back_rank + file)
+                    }
+                    _ => return Err(FenError::BadCastling(string.to_string()))
+                };
+                let right = CASTLE_RIGHTS[colour_index][side];
+                if chess.castling & right != 0 {return Err(FenError::BadCastling(string.to_string()))}
+                chess.castling |= right;
+                chess.castling_rooks[colour_index][side] = rook;
             }
         }
         // En passant target square
-        let fen_en = iter.next().expect("FEN en passant");
+        let fen_en = iter.next().ok_or(FenError::MissingField("en passant square"))?;
         if fen_en != "-" {
             let mut chars = fen_en.chars();
-            chess.en_passant = utils::square_from_text(chars.next().unwrap(), chars.next().unwrap());
+            match (chars.next(), chars.next(), chars.next()) {
+                (Some(letter @ 'a'..='h'), Some(number @ ('3' | '6')), None) => {
+                    chess.en_passant = utils::square_from_text(letter, number);
+                }
+                _ => return Err(FenError::BadEnPassant(fen_en.to_string())),
+            }
         }
         // Halfmove clock
         if let Some(fen_half) = iter.next() {
-            chess.half_move = fen_half.parse().unwrap();
+            chess.half_move = fen_half.parse().map_err(|_| FenError::BadClock(fen_half.to_string()))?;
         }
         // Fullmove counter
         if let Some(fen_full) = iter.next() {
-            chess.full_turn = fen_full.parse().unwrap();
+            chess.full_turn = fen_full.parse().map_err(|_| FenError::BadClock(fen_full.to_string()))?;
+        }
+        
+        Ok(chess)
+    }
+
+    // like from_fen, but also rejects positions that fail validate
+    pub fn from_fen_validated(fen: &str) -> Result<Chess, FenError> {
+        let chess = Chess::from_fen(fen)?;
+        let errors = chess.validate();
+        if !errors.is_empty() {return Err(FenError::IllegalPosition(errors))}
+        Ok(chess)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        // Piece Placement
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                let piece = self.board[rank * 8 + file];
+                if piece == NONE {
+                    empty += 1;
+                    continue;
+                }
+                if empty > 0 {
+                    fen += &empty.to_string();
+                    empty = 0;
+                }
+                fen.push(piece.symbol());
+            }
+            if empty > 0 {fen += &empty.to_string()}
+            if rank > 0 {fen.push('/')}
         }
+        // Side to move
+        fen += if self.is_white_to_move {" w "} else {" b "};
+        // Castling ability
+        if self.castling == CASTLE_NONE {fen.push('-')}
+        for colour in [WHITE, BLACK] {
+            for side in [KING_SIDE, QUEEN_SIDE] {
+                if self.castling & CASTLE_RIGHTS[colour.colour_index()][side] == 0 {continue}
+                // KQkq unless another rook stands further out, then the file of the rook
+                let rook = self.castling_rook(colour, side);
+                let back_rank = rook - rook % 8;
+                let mut beyond = if side == KING_SIDE {rook + 1..back_rank + 8} else {back_rank..rook};
+                let symbol = if beyond.any(|square| self.board(square) == Piece::new(ROOK, colour)) {
+                    (b'a' + (rook % 8) as u8) as char
+                } else if side == KING_SIDE {'k'} else {'q'};
+                fen.push(if colour == WHITE {symbol.to_ascii_uppercase()} else {symbol});
+            }
+        }
+        // En passant target square
+        fen.push(' ');
+        fen += &if self.en_passant == -1 {"-".to_string()} else {utils::square_to_text(self.en_passant)};
+        // Halfmove clock and fullmove counter
+        fen += &format!(" {} {}", self.half_move, self.full_turn);
         
-        return chess
+        fen
+    }
+}
+
+#[cfg(test)]
+mod fen_tests {
+    use super::*;
+
+    #[test]
+    fn to_fen_round_trip() {
+        for fen in [
+            START_POSITION,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "8/8/8/8/8/4k3/8/4K3 b - - 57 102",
+        ] {
+            let chess = Chess::build(fen);
+            assert_eq!(chess.to_fen(), fen);
+            assert_eq!(Chess::build(&chess.to_fen()), chess);
+        }
+    }
+
+    #[test]
+    fn chess960_castling_fields() {
+        // Shredder-FEN files are exported as X-FEN when the rook is the outermost one
+        let shredder = Chess::build("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9");
+        assert_eq!(shredder.to_fen(), "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9");
+        assert_eq!(Chess::build(&shredder.to_fen()), shredder);
+        assert_eq!(shredder.castling_rook(WHITE, QUEEN_SIDE), 5);
+        // an inner rook needs its file
+        let inner = Chess::build("rr2k3/8/8/8/8/8/8/RR2K3 w Bb - 0 1");
+        assert_eq!(inner.to_fen(), "rr2k3/8/8/8/8/8/8/RR2K3 w Bb - 0 1");
+        assert_eq!(inner.castling_rook(BLACK, QUEEN_SIDE), 57);
+        assert_eq!(Chess::build("rr2k3/8/8/8/8/8/8/RR2K3 w Qq - 0 1").castling_rook(WHITE, QUEEN_SIDE), 0);
+    }
+
+    #[test]
+    fn chess960_start_positions() {
+        assert_eq!(chess960_fen(518), START_POSITION);
+        assert_eq!(chess960_fen(0), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+        assert_eq!(chess960_fen(959), "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1");
+        for index in 0..960 {
+            assert_eq!(Chess::chess960(index).validate(), vec![], "{index}");
+        }
+    }
+
+    #[test]
+    fn to_fen_after_moves() {
+        let mut chess = Chess::start_position();
+        chess.make_move(Move::new(12, 28, DOUBLE_PUSH_FLAG));
+        assert_eq!(chess.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        chess.make_move(Move::new(62, 45, NO_FLAG));
+        chess.make_move(Move::new(4, 12, NO_FLAG));
+        assert_eq!(chess.to_fen(), "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 2 2");
+    }
+
+    #[test]
+    fn from_fen_errors() {
+        let errors = [
+            ("", FenError::MissingField("piece placement")),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR", FenError::MissingField("side to move")),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1", FenError::BadPlacement("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP".to_string())),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBXKBNR w KQkq - 0 1", FenError::BadPlacement("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBXKBNR".to_string())),
+            ("rnbqkbnr/pppppppp/8/8/8/7/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::BadPlacement("rnbqkbnr/pppppppp/8/8/8/7/PPPPPPPP/RNBQKBNR".to_string())),
+            ("rnbqkbnr/ppppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::RankTooLong(7)),
+            ("rnbqkbnr/pppppppp/8/8/44p/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::RankTooLong(4)),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1", FenError::BadSide("x".to_string())),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1", FenError::BadCastling("KQkx".to_string())),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KK - 0 1", FenError::BadCastling("KK".to_string())),
+            // a Shredder-FEN file needs a rook, not the king
+            ("4k3/8/8/8/8/8/8/R3K2R w E - 0 1", FenError::BadCastling("E".to_string())),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1", FenError::BadEnPassant("e4".to_string())),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1", FenError::BadClock("x".to_string())),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 -1", FenError::BadClock("-1".to_string())),
+        ];
+        for (fen, error) in errors {
+            assert_eq!(Chess::from_fen(fen), Err(error));
+        }
+        assert_eq!(Chess::from_fen(START_POSITION), Ok(Chess::start_position()));
+        assert_eq!(
+            Chess::from_fen_validated("4k3/8/8/8/8/8/8/4K2K w - - 0 1"),
+            Err(FenError::IllegalPosition(vec![PositionError::KingCount(WHITE, 2)]))
+        );
+        assert_eq!(Chess::from_fen_validated(START_POSITION), Ok(Chess::start_position()));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,86 @@
+use super::*;
+
+// what unmake_move can't work out from the position after the move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StateInfo {
+    pub captured: PieceType,
+    pub en_passant: Square,
+    pub castling: u8,
+    pub half_move: u16,
+    pub full_turn: u16,
+    pub hash: Hash,
+    pub checkers: Bitboard,
+}
+
+impl Chess {
+    // the state of the position about to be left by the move
+    pub(super) fn push_state(&mut self, captured: PieceType, r#move: Move) {
+        let checkers = if self.in_check() {self.checkers()} else {EMPTY};
+        self.history.push(StateInfo {
+            captured,
+            en_passant: self.en_passant,
+            castling: self.castling,
+            half_move: self.half_move,
+            full_turn: self.full_turn,
+            hash: self.hash(),
+            checkers,
+        });
+        self.moves.push(r#move);
+    }
+    pub(super) fn pop_state(&mut self) -> StateInfo {
+        self.moves.pop();
+        let state = self.history.pop().unwrap();
+        (self.en_passant, self.castling, self.half_move, self.full_turn) = (state.en_passant, state.castling, state.half_move, state.full_turn);
+        state
+    }
+
+    // the moves made since the position was set up, null moves as NONE_MOVE
+    pub fn moves_played(&self) -> &[Move] { &self.moves }
+    pub fn ply(&self) -> usize { self.moves.len() }
+
+    // takes back the last move played, None once back at the set up position
+    pub fn undo_last(&mut self) -> Option<Move> {
+        let r#move = *self.moves.last()?;
+        if r#move == NONE_MOVE {self.unmake_null_move()} else {self.unmake_move(r#move)}
+        Some(r#move)
+    }
+
+    // forgets the moves played, the position stays as it is
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.moves.clear();
+    }
+}
+
+#[cfg(test)]
+mod history_tests {
+    use super::*;
+
+    #[test]
+    fn undo_moves_played() {
+        let mut chess = Chess::build("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        let fen = chess.to_fen();
+        let texts = ["e5f6", "g8f6", "f1b5", "c7c6", "g1f3", "c6b5", "e1g1"];
+        for text in texts {
+            chess.make_move(Move::from_text(&chess, text));
+        }
+        assert_eq!(chess.ply(), texts.len());
+        assert_eq!(chess.moves_played().iter().map(|r#move| r#move.to_text()).collect::<Vec<_>>(), texts);
+
+        let last = chess.history.last().unwrap();
+        assert_eq!((last.captured, last.castling, last.half_move, last.full_turn), (NONE_TYPE, 15, 0, 6));
+        assert_eq!(chess.history[0].en_passant, 45);
+        // the bishop check was recorded in the position before c7c6
+        assert_eq!(chess.history[3].checkers, square_bb(33));
+        assert_eq!(chess.history[5].captured, BISHOP);
+
+        chess.make_null_move();
+        assert_eq!(chess.undo_last(), Some(NONE_MOVE));
+        for text in texts.iter().rev() {
+            assert_eq!(chess.undo_last().map(|r#move| r#move.to_text()), Some(text.to_string()));
+        }
+        assert_eq!(chess.undo_last(), None);
+        assert_eq!(chess.to_fen(), fen);
+        assert_eq!(chess.hash(), Chess::build(&fen).hash());
+    }
+}
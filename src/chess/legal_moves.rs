@@ -1,251 +1,306 @@
-use std::{cmp::min, sync::Mutex, str::FromStr};
-
 use super::*;
 
-static PRECOMPUTED: std::sync::Mutex<bool> = Mutex::new(false);
-
 pub const DIRECTION_OFFSETS: [i8;8] = [8,-8,1,-1,7,-7,9,-9];
 pub const DIRECTION_OPPOST_INDEX: [usize;8] = [1,0,3,2,5,4,7,6];
-pub static mut NUM_SQUARES_TO_EDGES: [[u8;8]; 64] = [[0;8]; 64];
-
-const NEW_VEC: Vec<Square> = Vec::new();
-pub static mut KING_ATTACKS: [Vec<Square>;64] = [NEW_VEC;64];
-pub static mut KNIGHT_ATTACKS: [Vec<Square>;64] = [NEW_VEC;64];
-pub static mut PAWN_ATTACKS: [[Vec<Square>;64]; 2] = [[NEW_VEC;64], [NEW_VEC;64]];
-
-pub fn piece_attacks(piece_type: PieceType, colour: Colour, square: Square) -> &'static mut Vec<Square> {
-    &mut (match piece_type {
-        KING => unsafe {&mut KING_ATTACKS},
-        KNIGHT => unsafe {&mut KNIGHT_ATTACKS},
-        PAWN => unsafe {&mut PAWN_ATTACKS[colour.colour_index()]},
-        _ => unreachable!(),
-    })[square as usize]
+pub static NUM_SQUARES_TO_EDGES: [[u8;8]; 64] = precomputed_move_data();
+
+pub static KING_ATTACKS: [SquareList;64] = precomputed_king_attacks();
+pub static KNIGHT_ATTACKS: [SquareList;64] = precomputed_knight_attacks();
+pub static PAWN_ATTACKS: [[SquareList;64]; 2] = precomputed_pawn_attacks();
+
+// up to 8 squares, fixed size so that the tables are built at compile time
+#[derive(Debug, Clone, Copy)]
+pub struct SquareList {
+    squares: [Square; 8],
+    len: usize,
 }
-pub fn precompute() { // mutex because rust is bugged on tests
-    let mut precomputed = PRECOMPUTED.lock().unwrap();
-    if *precomputed {return}
-    *precomputed = true;
-    
-    let path = PathBuf::from_str(&std::env::var("HOME").unwrap()).unwrap().join(".chess-AI");
-    let _ = std::fs::create_dir(&path);
-    *ROOT_PATH.lock().unwrap() = Some(path);
-
-    clear_log();
-    precomputed_move_data();
-    precomputed_king_attacks();
-    precomputed_knight_attacks();
-    precomputed_pawn_attacks();
+impl SquareList {
+    const EMPTY: SquareList = SquareList { squares: [0; 8], len: 0 };
+    const fn push(&mut self, square: Square) {
+        self.squares[self.len] = square;
+        self.len += 1;
+    }
 }
-fn precomputed_move_data() {
-    for rank in 0..8 {
-        for file in 0..8 {
-            let num_north = 7 - rank;
-            let num_south = rank;
-            let num_east = 7 - file;
-            let num_west = file;
-            unsafe {
-                NUM_SQUARES_TO_EDGES[(rank * 8 + file) as usize] = [
-                    num_north,
-                    num_south,
-                    num_east,
-                    num_west,
-                    min(num_north, num_west),
-                    min(num_south, num_east),
-                    min(num_north, num_east),
-                    min(num_south, num_west),
-                ];
-            }
-        }
+impl std::ops::Deref for SquareList {
+    type Target = [Square];
+    fn deref(&self) -> &[Square] { &self.squares[..self.len] }
+}
+impl<'a> IntoIterator for &'a SquareList {
+    type Item = &'a Square;
+    type IntoIter = std::slice::Iter<'a, Square>;
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+pub fn piece_attacks(piece_type: PieceType, colour: Colour, square: Square) -> &'static [Square] {
+    match piece_type {
+        KING => &KING_ATTACKS[square as usize],
+        KNIGHT => &KNIGHT_ATTACKS[square as usize],
+        PAWN => &PAWN_ATTACKS[colour.colour_index()][square as usize],
+        _ => unreachable!(),
     }
 }
-fn precomputed_king_attacks() {
-    for square in 0..64 {
-        for dir_index in 0..8 {
-            let dist_edge = unsafe {NUM_SQUARES_TO_EDGES[square as usize][dir_index]};
-            if dist_edge >= 1 {
-                let target = square + DIRECTION_OFFSETS[dir_index];
-                unsafe {KING_ATTACKS[square as usize].push(target)};
-            }
+const fn min(a: u8, b: u8) -> u8 { if a < b {a} else {b} }
+const fn precomputed_move_data() -> [[u8;8]; 64] {
+    let mut edges = [[0;8]; 64];
+    let mut square = 0;
+    while square < 64 {
+        let (rank, file) = (square as u8 / 8, square as u8 % 8);
+        let num_north = 7 - rank;
+        let num_south = rank;
+        let num_east = 7 - file;
+        let num_west = file;
+        edges[square] = [
+            num_north,
+            num_south,
+            num_east,
+            num_west,
+            min(num_north, num_west),
+            min(num_south, num_east),
+            min(num_north, num_east),
+            min(num_south, num_west),
+        ];
+        square += 1;
+    }
+    edges
+}
+const fn precomputed_king_attacks() -> [SquareList;64] {
+    let mut attacks = [SquareList::EMPTY; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut dir_index = 0;
+        while dir_index < 8 {
+            if NUM_SQUARES_TO_EDGES[square][dir_index] >= 1 {
+                attacks[square].push(square as Square + DIRECTION_OFFSETS[dir_index]);
+            }
+            dir_index += 1;
         }
+        square += 1;
     }
+    attacks
 }
-fn precomputed_knight_attacks() {
-    for square in 0..64 {
-        for jump in [-17, -15, -10, -6, 6, 10, 15, 17] {
-            let target = square + jump;
-            if target >= 64 || target < 0 {continue}
-            if square % 8 - target % 8 > 2 {continue}
-            if square % 8 - target % 8 < -2 {continue}
-            unsafe {KNIGHT_ATTACKS[square as usize].push(target)};
+const fn precomputed_knight_attacks() -> [SquareList;64] {
+    let mut attacks = [SquareList::EMPTY; 64];
+    let jumps = [-17, -15, -10, -6, 6, 10, 15, 17];
+    let mut square = 0;
+    while square < 64 {
+        let mut jump_index = 0;
+        while jump_index < 8 {
+            let target = square as Square + jumps[jump_index];
+            let file_distance = (square as Square % 8 - target % 8).abs();
+            if target >= 0 && target < 64 && file_distance <= 2 {
+                attacks[square].push(target);
+            }
+            jump_index += 1;
         }
+        square += 1;
     }
+    attacks
 }
-fn precomputed_pawn_attacks() {
-    for square in 0..64 {
-        for (colour_index, attacks) in [[4, 6], [5, 7]].iter().enumerate() {
-            for &dir_index in attacks {
-                let dist_edge = unsafe {NUM_SQUARES_TO_EDGES[square as usize][dir_index]};
-                if dist_edge >= 1 {
-                    let target = square + DIRECTION_OFFSETS[dir_index];
-                    unsafe {PAWN_ATTACKS[colour_index][square as usize].push(target)};
+const fn precomputed_pawn_attacks() -> [[SquareList;64]; 2] {
+    let mut attacks = [[SquareList::EMPTY; 64]; 2];
+    let dir_indices = [[4, 6], [5, 7]];
+    let mut square = 0;
+    while square < 64 {
+        let mut colour_index = 0;
+        while colour_index < 2 {
+            let mut n = 0;
+            while n < 2 {
+                let dir_index = dir_indices[colour_index][n];
+                if NUM_SQUARES_TO_EDGES[square][dir_index] >= 1 {
+                    attacks[colour_index][square].push(square as Square + DIRECTION_OFFSETS[dir_index]);
                 }
+                n += 1;
             }
+            colour_index += 1;
         }
+        square += 1;
+    }
+    attacks
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenType {
+    All,
+    Captures, // captures, en passant and every promotion
+    Quiets, // everything else, castling included
+    Evasions, // all the moves, only when in check
+    QuietChecks,
+}
+impl GenType {
+    fn captures(self) -> bool { matches!(self, GenType::All | GenType::Captures | GenType::Evasions) }
+    fn quiets(self) -> bool { !matches!(self, GenType::Captures) }
+}
+
+// pinned pieces with the squares they can still move to, at most one pin per direction
+#[derive(Default)]
+pub(super) struct Pins {
+    pub(super) pinned: Bitboard,
+    lines: [(Square, Bitboard); 8],
+    len: usize,
+}
+impl Pins {
+    fn push(&mut self, square: Square, line: Bitboard) {
+        self.pinned |= square_bb(square);
+        self.lines[self.len] = (square, line);
+        self.len += 1;
+    }
+    fn is_pinned(&self, square: Square) -> bool { has_square(self.pinned, square) }
+    fn line(&self, square: Square) -> Option<Bitboard> {
+        if !self.is_pinned(square) {return None}
+        self.lines[..self.len].iter().find(|pin| pin.0 == square).map(|pin| pin.1)
     }
 }
 
 impl Chess {
-    pub fn generate_legal_moves(&self) -> Vec<Move> {
+    pub fn generate_legal_moves(&self) -> MoveList {
+        self.generate_moves(GenType::All)
+    }
+
+    pub fn generate_moves(&self, gen_type: GenType) -> MoveList {
+        debug_assert!(gen_type != GenType::Evasions || self.in_check());
 
         let colour = self.colour_to_move();
         let colour_index = self.colour_index();
         let opponent_index = self.opponent_index();
         let king = self.side[colour_index].king;
 
-        let (mut pins, attack) = self.get_pins_and_slide_attack(king, colour);
+        let (pins, attack) = self.get_pins_and_slide_attack(king, colour);
         let is_en_passant_pinned = self.is_en_passant_pinned();
 
-        let mut moves = Vec::new();
+        let mut targets = EMPTY;
+        if gen_type.captures() {targets |= self.colour_bb(colour.opponent())}
+        if gen_type.quiets() {targets |= !self.occupied_bb()}
+
+        let mut moves = MoveList::new();
         
-        self.generate_king_moves(&mut moves);
-
-        if !self.is_king_in_check() {
-            let sliding_pieces = self.side[colour_index].queens.iter();
-            let piece_type = QUEEN;
-            for &start in sliding_pieces {
-                if let Some(pin_index) = pins.iter().position(|pin| pin.0 == start) {
-                    let defend_squares = pins.swap_remove(pin_index).1;
-                    self.generate_sliding_defenses(&mut moves, start, piece_type, &defend_squares);
-                } else {
-                    self.generate_sliding_moves(&mut moves, start, piece_type);
-                }
-            }
-            let sliding_pieces = self.side[colour_index].rooks.iter();
-            let piece_type = ROOK;
-            for &start in sliding_pieces {
-                if let Some(pin_index) = pins.iter().position(|pin| pin.0 == start) {
-                    let defend_squares = pins.swap_remove(pin_index).1;
-                    self.generate_sliding_defenses(&mut moves, start, piece_type, &defend_squares);
-                } else {
-                    self.generate_sliding_moves(&mut moves, start, piece_type);
+        self.generate_king_moves(&mut moves, targets, gen_type);
+
+        let king_treats = self.get_king_treats();
+        if king_treats == 0 {
+            for piece_type in [QUEEN, ROOK, BISHOP] {
+                for &start in self.side[colour_index].pieces(piece_type) {
+                    if let Some(defend_squares) = pins.line(start) {
+                        self.generate_sliding_moves(&mut moves, start, piece_type, defend_squares & targets);
+                    } else {
+                        self.generate_sliding_moves(&mut moves, start, piece_type, targets);
+                    }
                 }
             }
-            let sliding_pieces = self.side[colour_index].bishops.iter();
-            let piece_type = BISHOP;
-            for &start in sliding_pieces {
-                if let Some(pin_index) = pins.iter().position(|pin| pin.0 == start) {
-                    let defend_squares = pins.swap_remove(pin_index).1;
-                    self.generate_sliding_defenses(&mut moves, start, piece_type, &defend_squares);
-                } else {
-                    self.generate_sliding_moves(&mut moves, start, piece_type);
+            for &start in &self.side[colour_index].knights {
+                if !pins.is_pinned(start) {
+                    self.generate_knight_moves(&mut moves, start, targets);
                 }
             }
-            let sliding_pieces = self.side[colour_index].knights.iter();
-            for &start in sliding_pieces {
-                if let Some(pin_index) = pins.iter().position(|pin| pin.0 == start) {
-                    let defend_squares = pins.swap_remove(pin_index).1;
-                    self.generate_knight_defenses(&mut moves, start, &defend_squares);
+            for &start in &self.side[colour_index].pawns {
+                if let Some(defend_squares) = pins.line(start) {
+                    // a pawn pinned on a diagonal can still take en passant along it
+                    let is_en_passant_allowed = self.en_passant != -1 && has_square(defend_squares, self.en_passant) && !is_en_passant_pinned;
+                    self.generate_pawn_moves(&mut moves, start, is_en_passant_allowed, defend_squares, gen_type);
                 } else {
-                    self.generate_knight_moves(&mut moves, start);
-                }
-            }
-            let sliding_pieces = self.side[colour_index].pawns.iter();
-            for &start in sliding_pieces {
-                if let Some(pin_index) = pins.iter().position(|pin| pin.0 == start) {
-                    let defend_squares = pins.swap_remove(pin_index).1;
-                    self.generate_pawn_defenses(&mut moves, start, false, &defend_squares);
-                } else {
-                    self.generate_pawn_moves(&mut moves, start, is_en_passant_pinned);
+                    self.generate_pawn_moves(&mut moves, start, !is_en_passant_pinned, !EMPTY, gen_type);
                 }
             }
         }
-        else if self.get_king_treats() == 1 {
-            let defend_squares = attack.unwrap_or_else(|| Vec::from([
-                if self.side[opponent_index].piece_attacks[KNIGHT.piece_index()][king as usize] == 1
-                {self.find_knight_check()}
-                else
-                {self.find_pawn_check()}
-                ]));
-            let is_en_passant_defense = self.side[opponent_index].piece_attacks[PAWN.piece_index()][king as usize] == 1;
+        else if king_treats == 1 {
+            let (defend_squares, is_pawn_check) = if self.attack_maps {
+                let defend_squares = attack.unwrap_or_else(|| square_bb(
+                    if self.side[opponent_index].piece_attacks[KNIGHT.piece_index()][king as usize] == 1
+                    {self.find_knight_check()}
+                    else
+                    {self.find_pawn_check()}
+                    ));
+                (defend_squares, self.side[opponent_index].piece_attacks[PAWN.piece_index()][king as usize] == 1)
+            } else {
+                let checker = self.checkers();
+                (attack.unwrap_or(checker), checker & self.pieces_bb(colour.opponent(), PAWN) != EMPTY)
+            };
+            let is_en_passant_defense = is_pawn_check && !is_en_passant_pinned;
             
-            let sliding_pieces = self.side[colour_index].queens.iter();
-            let piece_type = QUEEN;
-            for &start in sliding_pieces {
-                if !pins.iter().any(|pin| pin.0 == start) {
-                    self.generate_sliding_defenses(&mut moves, start, piece_type, &defend_squares);
-                }
-            }
-            let sliding_pieces = self.side[colour_index].rooks.iter();
-            let piece_type = ROOK;
-            for &start in sliding_pieces {
-                if !pins.iter().any(|pin| pin.0 == start) {
-                    self.generate_sliding_defenses(&mut moves, start, piece_type, &defend_squares);
-                }
-            }
-            let sliding_pieces = self.side[colour_index].bishops.iter();
-            let piece_type = BISHOP;
-            for &start in sliding_pieces {
-                if !pins.iter().any(|pin| pin.0 == start) {
-                    self.generate_sliding_defenses(&mut moves, start, piece_type, &defend_squares);
+            for piece_type in [QUEEN, ROOK, BISHOP] {
+                for &start in self.side[colour_index].pieces(piece_type) {
+                    if !pins.is_pinned(start) {
+                        self.generate_sliding_moves(&mut moves, start, piece_type, defend_squares & targets);
+                    }
                 }
             }
-            let sliding_pieces = self.side[colour_index].knights.iter();
-            for &start in sliding_pieces {
-                if !pins.iter().any(|pin| pin.0 == start) {
-                    self.generate_knight_defenses(&mut moves, start, &defend_squares);
+            for &start in &self.side[colour_index].knights {
+                if !pins.is_pinned(start) {
+                    self.generate_knight_moves(&mut moves, start, defend_squares & targets);
                 }
             }
-            let sliding_pieces = self.side[colour_index].pawns.iter();
-            for &start in sliding_pieces {
-                if !pins.iter().any(|pin| pin.0 == start) {
-                    self.generate_pawn_defenses(&mut moves, start, is_en_passant_defense, &defend_squares);
+            for &start in &self.side[colour_index].pawns {
+                if !pins.is_pinned(start) {
+                    self.generate_pawn_moves(&mut moves, start, is_en_passant_defense, defend_squares, gen_type);
                 }
             }
         }
+        if gen_type == GenType::QuietChecks {
+            moves.retain(|&r#move| self.gives_check(r#move));
+        }
         moves
     }
 
-    fn get_pins_and_slide_attack(&self, square: Square, colour: Colour) -> (Vec<(Square, Vec<Square>)>, Option<Vec<Square>>) {
-        let mut pins = Vec::new();
+    // direct and discovered checks, looking at the board after the move
+    pub fn gives_check(&self, r#move: Move) -> bool {
+        let colour = self.colour_to_move();
+        let opponent_king = self.side[self.opponent_index()].king;
+        let (start, target) = (r#move.start(), r#move.target());
+
+        let mut piece_type = self.board[start as usize].get_type();
+        let mut moved = square_bb(start);
+        let mut landing = target;
+        let mut occupied = self.occupied_bb() & !square_bb(start) | square_bb(target);
+        match r#move.flag() {
+            EN_PASSANT_FLAG => {
+                let eaten_square = if self.is_white_to_move {target - 8} else {target + 8};
+                occupied ^= square_bb(eaten_square);
+            }
+            CASTLE_FLAG => {
+                let (_, rook, _, rook_target) = self.castle_squares(self.colour_index(), r#move);
+                occupied = occupied & !square_bb(rook) | square_bb(target) | square_bb(rook_target);
+                piece_type = ROOK;
+                moved |= square_bb(rook);
+                landing = rook_target;
+            }
+            NO_FLAG | DOUBLE_PUSH_FLAG => (),
+            _ => piece_type = r#move.promotion_type(),
+        }
+        let queens = self.pieces_bb(colour, QUEEN);
+        let mut orthogonal_sliders = (self.pieces_bb(colour, ROOK) | queens) & !moved;
+        let mut diagonal_sliders = (self.pieces_bb(colour, BISHOP) | queens) & !moved;
+        if piece_type == ROOK || piece_type == QUEEN {orthogonal_sliders |= square_bb(landing)}
+        if piece_type == BISHOP || piece_type == QUEEN {diagonal_sliders |= square_bb(landing)}
+
+        if rook_attacks(opponent_king, occupied) & orthogonal_sliders != EMPTY
+        || bishop_attacks(opponent_king, occupied) & diagonal_sliders != EMPTY {
+            return true;
+        }
+        match piece_type {
+            KNIGHT | PAWN => piece_attacks(piece_type, colour, landing).contains(&opponent_king),
+            _ => false,
+        }
+    }
+
+    pub(super) fn get_pins_and_slide_attack(&self, square: Square, colour: Colour) -> (Pins, Option<Bitboard>) {
+        let mut pins = Pins::default();
         let mut attack = None;
 
-        for dir_index in 0..8 {
-            let dist_edge = unsafe {NUM_SQUARES_TO_EDGES[square as usize][dir_index]};
-            let mut defend_squares = Vec::new();
+        let opponent = colour.opponent();
+        let occupied = self.occupied_bb();
+        let queens = self.pieces_bb(opponent, QUEEN);
+        let snipers = rook_attacks(square, EMPTY) & (self.pieces_bb(opponent, ROOK) | queens)
+            | bishop_attacks(square, EMPTY) & (self.pieces_bb(opponent, BISHOP) | queens);
 
-            for n in 0..dist_edge {
-                let pinned_square = square + DIRECTION_OFFSETS[dir_index] * (n+1) as i8;
-                let pinned_piece = self.board[pinned_square as usize];
-                
-                defend_squares.push(pinned_square);
-                if pinned_piece == NONE {continue}
-
-                if pinned_piece.is_colour(colour) {
-
-                    for n2 in (n+1)..dist_edge {
-                        let target_square = square + DIRECTION_OFFSETS[dir_index] * (n2+1) as i8;
-                        let target_piece = self.board[target_square as usize];
-                        
-                        defend_squares.push(target_square);
-                        if target_piece == NONE {continue}
-
-                        if target_piece.is_colour(colour.opponent()) {
-                            if target_piece.get_type().is_sliding()
-                            && target_piece.get_type().get_sliding_indices().contains(&dir_index) {
-                                pins.push((pinned_square, defend_squares));
-                            }
-                        }
-                        break;
-                    }
+        for sniper in squares(snipers) {
+            let line = between(square, sniper);
+            let blockers = line & occupied;
+            let defend_squares = line | square_bb(sniper);
 
-                }
-                else {
-                    if pinned_piece.get_type().is_sliding()
-                    && pinned_piece.get_type().get_sliding_indices().contains(&dir_index) {
-                        attack = Some(defend_squares);
-                    }
-                }
-                break;
+            if blockers == EMPTY {
+                attack = Some(defend_squares);
+            }
+            else if blockers.count_ones() == 1 && blockers & self.colour_bb(colour) != EMPTY {
+                pins.push(lsb(blockers), defend_squares);
             }
         }
         (pins, attack)
@@ -255,92 +310,42 @@ impl Chess {
         if self.en_passant == -1 {return false}
         
         let colour = self.colour_to_move();
+        let opponent = colour.opponent();
         let king = self.side[self.colour_index()].king;
         let rank = if self.is_white_to_move {4} else {3};
         
         if king / 8 != rank {return false}
-        
-        let mut pinned_pawn = -1;
-        let dir_index = if king % 8 > self.en_passant % 8 {3} else {2};
-        
-        let dist_edge = unsafe {NUM_SQUARES_TO_EDGES[king as usize][dir_index]};
-        
-        for n in 0..dist_edge {
-            let target_square = king + DIRECTION_OFFSETS[dir_index] * (n+1) as i8;
-            let target_piece = self.board[target_square as usize];
-
-            if target_piece == NONE {continue}
-            
-            if target_piece.get_type() == PAWN {
 
-                if target_piece.is_colour(colour) {
-                    if pinned_pawn == -1 {
-                        pinned_pawn = target_square;
-                        continue;
-                    }
-                } else {
-                    if target_square % 8 == self.en_passant % 8 {
-                        continue;
-                    }
-                }
+        let eaten_square = if self.is_white_to_move {self.en_passant - 8} else {self.en_passant + 8};
+        let rank_bb = RANK_1 << (8 * rank);
+        let sliders = (self.pieces_bb(opponent, ROOK) | self.pieces_bb(opponent, QUEEN)) & rank_bb;
+        let capturing_pawns = self.pieces_bb(colour, PAWN) & rank_bb
+            & (square_bb(eaten_square + 1) & !FILE_A | square_bb(eaten_square - 1) & !FILE_H);
 
-            }
-            else if !target_piece.is_colour(colour)
-            && pinned_pawn != -1 {
-                if target_piece.get_type().is_sliding()
-                && target_piece.get_type().get_sliding_indices().contains(&dir_index) {
-                    return true;
-                }
-            }
-            break;
-        }
-        return false;
+        squares(capturing_pawns).any(|pawn| {
+            let occupied = self.occupied_bb() ^ square_bb(pawn) ^ square_bb(eaten_square);
+            rook_attacks(king, occupied) & sliders != EMPTY
+        })
     }
 
-    fn generate_sliding_moves(&self, moves: &mut Vec<Move>, start: Square, piece: PieceType) {
-        for dir_index in piece.get_sliding_indices() {
-            let dist_edge = unsafe {NUM_SQUARES_TO_EDGES[start as usize][dir_index]};
-
-            for n in 0..dist_edge {
-                let target = start + DIRECTION_OFFSETS[dir_index] * (n + 1) as i8;
-                let target_piece = self.board[target as usize];
-
-                if target_piece.is_colour(self.colour_to_move()) {break}
-
-                moves.push(Move::new(start, target, NO_FLAG));
-
-                if target_piece != NONE {break}
-            }
-        }
-    }
-    fn generate_sliding_defenses(&self, moves: &mut Vec<Move>, start: Square, piece: PieceType, defend_squares: &Vec<Square>) {
-        for dir_index in piece.get_sliding_indices() {
-            let dist_edge = unsafe {NUM_SQUARES_TO_EDGES[start as usize][dir_index]};
-            for n in 0..dist_edge {
-                let target = start + DIRECTION_OFFSETS[dir_index] * (n + 1) as i8;
-                let target_piece = self.board[target as usize];
-
-                if target_piece.is_colour(self.colour_to_move()) {break}
-
-                if defend_squares.contains(&target) {
-                    moves.push(Move::new(start, target, NO_FLAG));
-                }
-
-                if target_piece != NONE {break}
-            }
+    fn generate_sliding_moves(&self, moves: &mut MoveList, start: Square, piece: PieceType, targets: Bitboard) {
+        for target in squares(slider_attacks(piece, start, self.occupied_bb()) & targets) {
+            moves.push(Move::new(start, target, NO_FLAG));
         }
     }
 
-    fn generate_pawn_moves(&self, moves: &mut Vec<Move>, start: Square, is_en_passant_pinned: bool) {
+    fn generate_pawn_moves(&self, moves: &mut MoveList, start: Square, is_en_passant_allowed: bool, defend_squares: Bitboard, gen_type: GenType) {
         let opponent_color = self.colour_to_move().opponent();
 
         let (double_push_rank, promotion_rank, pawn_heading) = if self.is_white_to_move {(1,6,8)} else {(6,1,-8)};
-        let pawn_attacks = unsafe {&PAWN_ATTACKS[self.colour_index()]};
+        let pawn_attacks = &PAWN_ATTACKS[self.colour_index()];
 
         if start / 8 == promotion_rank {
+            if !gen_type.captures() {return}
 
             for &target in &pawn_attacks[start as usize] {
-                if self.board[target as usize].is_colour(opponent_color) {
+                if self.board[target as usize].is_colour(opponent_color)
+                && has_square(defend_squares, target) {
                     for promotion_flag in [PROMOTE_TO_QUEEN_FLAG, PROMOTE_TO_KNIGHT_FLAG, PROMOTE_TO_ROOK_FLAG, PROMOTE_TO_BISHOP_FLAG] {
                         moves.push(Move::new(start, target, promotion_flag));
                     }
@@ -348,7 +353,8 @@ impl Chess {
             }
             let target = start + pawn_heading;
 
-            if self.board[target as usize] == NONE {
+            if self.board[target as usize] == NONE
+            && has_square(defend_squares, target) {
                 for promotion_flag in [PROMOTE_TO_QUEEN_FLAG, PROMOTE_TO_KNIGHT_FLAG, PROMOTE_TO_ROOK_FLAG, PROMOTE_TO_BISHOP_FLAG] {
                     moves.push(Move::new(start, target, promotion_flag));
                 }
@@ -357,84 +363,32 @@ impl Chess {
         }
         else {
 
-            for &target in &pawn_attacks[start as usize] {
-                if self.board[target as usize].is_colour(opponent_color) {
-                    moves.push(Move::new(start, target, NO_FLAG));
-                }
-                else if target == self.en_passant && !is_en_passant_pinned {
-                    moves.push(Move::new(start, target, EN_PASSANT_FLAG));
-                }
-            }
-            let target = start + pawn_heading;
-
-            if self.board[target as usize] == NONE {
-                moves.push(Move::new(start, target, NO_FLAG));
-                
-                if start / 8 == double_push_rank {
-                    let target = target + pawn_heading;
-
-                    if self.board[target as usize] == NONE {
-                        moves.push(Move::new(start, target, DOUBLE_PUSH_FLAG));
-                    }
-                }
-            }
-        }
-    }
-    fn generate_pawn_defenses(&self, moves: &mut Vec<Move>, start: Square, is_en_passant_defense: bool, defend_squares: &Vec<Square>) {
-        let opponent_color = self.colour_to_move().opponent();
-
-        let (double_push_rank, promotion_rank, pawn_heading) = if self.is_white_to_move {(1,6,8)} else {(6,1,-8)};
-        let pawn_attacks = unsafe {&PAWN_ATTACKS[self.colour_index()]};
-
-        if start / 8 == promotion_rank {
-
-            for &target in &pawn_attacks[start as usize] {
-                if self.board[target as usize].is_colour(opponent_color) {
-                    if defend_squares.contains(&target) {
-                        for promotion_flag in [PROMOTE_TO_QUEEN_FLAG, PROMOTE_TO_KNIGHT_FLAG, PROMOTE_TO_ROOK_FLAG, PROMOTE_TO_BISHOP_FLAG] {
-                            moves.push(Move::new(start, target, promotion_flag));
-                        }
-                    }
-                }
-            }
-            let target = start + pawn_heading;
-
-            if self.board[target as usize] == NONE {
-                if defend_squares.contains(&target) {
-                    for promotion_flag in [PROMOTE_TO_QUEEN_FLAG, PROMOTE_TO_KNIGHT_FLAG, PROMOTE_TO_ROOK_FLAG, PROMOTE_TO_BISHOP_FLAG] {
-                        moves.push(Move::new(start, target, promotion_flag));
-                    }
-                }
-            }
-
-        }
-        else {
-
-            for &target in &pawn_attacks[start as usize] {
-                if defend_squares.contains(&target) {
-                    if self.board[target as usize].is_colour(opponent_color) {
+            if gen_type.captures() {
+                for &target in &pawn_attacks[start as usize] {
+                    if self.board[target as usize].is_colour(opponent_color)
+                    && has_square(defend_squares, target) {
                         moves.push(Move::new(start, target, NO_FLAG));
                     }
-                }
-                if target == self.en_passant && is_en_passant_defense {
-                    moves.push(Move::new(start, target, EN_PASSANT_FLAG));
+                    else if target == self.en_passant && is_en_passant_allowed {
+                        moves.push(Move::new(start, target, EN_PASSANT_FLAG));
+                    }
                 }
             }
+            if !gen_type.quiets() {return}
             let target = start + pawn_heading;
 
             if self.board[target as usize] == NONE {
 
-                if defend_squares.contains(&target) {
+                if has_square(defend_squares, target) {
                     moves.push(Move::new(start, target, NO_FLAG));
                 }
                 
                 if start / 8 == double_push_rank {
                     let target = target + pawn_heading;
 
-                    if self.board[target as usize] == NONE {
-                        if defend_squares.contains(&target) {
-                            moves.push(Move::new(start, target, DOUBLE_PUSH_FLAG));
-                        }
+                    if self.board[target as usize] == NONE
+                    && has_square(defend_squares, target) {
+                        moves.push(Move::new(start, target, DOUBLE_PUSH_FLAG));
                     }
                 }
             }
@@ -442,7 +396,7 @@ impl Chess {
     }
     
     fn find_pawn_check(&self) -> Square {
-        let pawn_attacks = unsafe {&PAWN_ATTACKS[self.colour_index()]};
+        let pawn_attacks = &PAWN_ATTACKS[self.colour_index()];
         for &target in &pawn_attacks[self.get_king_square() as usize] {
             if self.board[target as usize] == Piece::new(PAWN, self.colour_to_move().opponent()) {
                 return target;
@@ -458,22 +412,15 @@ impl Chess {
         unreachable!()
     }
 
-    fn generate_knight_moves(&self, moves: &mut Vec<Move>, start: Square) {
-        for &target in unsafe {&KNIGHT_ATTACKS[start as usize]} {
-            if !self.board[target as usize].is_colour(self.colour_to_move()) {
-                moves.push(Move::new(start, target, NO_FLAG));
-            }
-        }
-    }
-    fn generate_knight_defenses(&self, moves: &mut Vec<Move>, start: Square, defend_squares: &Vec<Square>) {
-        for &target in unsafe {&KNIGHT_ATTACKS[start as usize]} {
-            if defend_squares.contains(&target) {
+    fn generate_knight_moves(&self, moves: &mut MoveList, start: Square, targets: Bitboard) {
+        for &target in &KNIGHT_ATTACKS[start as usize] {
+            if has_square(targets, target) {
                 moves.push(Move::new(start, target, NO_FLAG));
             }
         }
     }
     fn find_knight_check(&self) -> Square {
-        for &target in unsafe {&KNIGHT_ATTACKS[self.get_king_square() as usize]} {
+        for &target in &KNIGHT_ATTACKS[self.get_king_square() as usize] {
             if self.board[target as usize] == Piece::new(KNIGHT, self.colour_to_move().opponent()) {
                 return target;
             }
@@ -483,58 +430,93 @@ impl Chess {
         unreachable!()
     }
     
-    fn generate_king_moves(&self, moves: &mut Vec<Move>) {
-        let colour = self.colour_to_move();
+    fn generate_king_moves(&self, moves: &mut MoveList, targets: Bitboard, gen_type: GenType) {
         let opponent_attacks = &self.side[self.opponent_index()].attacks;
         let start = self.side[self.colour_index()].king;
-
-        for &target in unsafe {&KING_ATTACKS[start as usize]} {
-            if !self.board[target as usize].is_colour(colour)
-            && opponent_attacks[target as usize] == 0 {
+        // the sliders look through the king, it can't step back along their line
+        let occupied = self.occupied_bb() & !square_bb(start);
+        let opponent = self.colour_bb(self.colour_to_move().opponent());
+        let is_attacked = |target: Square| if self.attack_maps {
+            opponent_attacks[target as usize] != 0
+        } else {
+            self.attackers_to_occupied(target, occupied) & opponent != EMPTY
+        };
+
+        for &target in &KING_ATTACKS[start as usize] {
+            if has_square(targets, target)
+            && !is_attacked(target) {
                 moves.push(Move::new(start, target, NO_FLAG))
             }
         }
-        if self.is_white_to_move {
-            if self.castling & CASTLE_WHITE_KING != 0 {
-                if self.board[5] == NONE
-                && self.board[6] == NONE
-                && opponent_attacks[4] == 0
-                && opponent_attacks[5] == 0
-                && opponent_attacks[6] == 0 {
-                    moves.push(Move::new(4, 6, CASTLE_FLAG));
-                }
-            }
-            if self.castling & CASTLE_WHITE_QUEEN != 0 {
-                if self.board[1] == NONE
-                && self.board[2] == NONE
-                && self.board[3] == NONE
-                && opponent_attacks[2] == 0
-                && opponent_attacks[3] == 0
-                && opponent_attacks[4] == 0 {
-                    moves.push(Move::new(4, 2, CASTLE_FLAG));
-                }
+        if !gen_type.quiets() {return}
+        for side in [KING_SIDE, QUEEN_SIDE] {
+            if let Some(castle) = self.castle_move(side) {
+                moves.push(castle);
             }
         }
-        else {
-            if self.castling & CASTLE_BLACK_KING != 0 {
-                if self.board[61] == NONE
-                && self.board[62] == NONE
-                && opponent_attacks[60] == 0
-                && opponent_attacks[61] == 0
-                && opponent_attacks[62] == 0 {
-                    moves.push(Move::new(60, 62, CASTLE_FLAG));
-                }
-            }
-            if self.castling & CASTLE_BLACK_QUEEN != 0 {
-                if self.board[57] == NONE
-                && self.board[58] == NONE
-                && self.board[59] == NONE
-                && opponent_attacks[58] == 0
-                && opponent_attacks[59] == 0
-                && opponent_attacks[60] == 0 {
-                    moves.push(Move::new(60, 58, CASTLE_FLAG));
-                }
-            }
+    }
+
+    // the castling move towards the side, if it can be played right now
+    pub(super) fn castle_move(&self, side: usize) -> Option<Move> {
+        let colour_index = self.colour_index();
+        if self.castling & CASTLE_RIGHTS[colour_index][side] == 0 {return None}
+        let opponent_attacks = &self.side[self.opponent_index()].attacks;
+        let opponent = self.colour_to_move().opponent();
+        let occupied = self.occupied_bb();
+        let back_rank = if self.is_white_to_move {0} else {56};
+        let king_target = back_rank + if side == KING_SIDE {6} else {2};
+        let castle = Move::new(self.side[colour_index].king, king_target, CASTLE_FLAG);
+        let (king, rook, king_target, rook_target) = self.castle_squares(colour_index, castle);
+
+        // only the king and the castling rook may stand where they pass
+        let castlers = square_bb(king) | square_bb(rook);
+        let path = between(king, king_target) | square_bb(king_target) | between(rook, rook_target) | square_bb(rook_target);
+        if path & occupied & !castlers != EMPTY {return None}
+
+        let king_path = between(king, king_target) | square_bb(king) | square_bb(king_target);
+        let is_attacked = |square: Square| if self.attack_maps {opponent_attacks[square as usize] != 0} else {self.is_square_attacked(square, opponent)};
+        if squares(king_path).any(is_attacked) {return None}
+        // the rook may be the one shielding the king target on the back rank
+        let occupied_after = occupied & !castlers | square_bb(king_target) | square_bb(rook_target);
+        if rook_attacks(king_target, occupied_after) & (self.pieces_bb(opponent, ROOK) | self.pieces_bb(opponent, QUEEN)) != EMPTY {return None}
+
+        Some(castle)
+    }
+}
+#[cfg(test)]
+mod gen_type_tests {
+    use super::*;
+
+    fn check_gen_types(chess: &mut Chess, depth: u16) {
+        let mut all = chess.generate_legal_moves();
+        let mut split = chess.generate_moves(GenType::Captures);
+        split.extend(chess.generate_moves(GenType::Quiets));
+        all.sort_by_key(|r#move| r#move.to_text());
+        split.sort_by_key(|r#move| r#move.to_text());
+        assert_eq!(all[..], split[..]);
+
+        let quiet_checks = chess.generate_moves(GenType::QuietChecks);
+        for r#move in chess.generate_moves(GenType::Quiets) {
+            chess.make_move(r#move);
+            assert_eq!(chess.in_check(), quiet_checks.contains(&r#move), "{}", r#move.to_text());
+            chess.unmake_move(r#move);
+        }
+        if chess.in_check() {
+            assert_eq!(chess.generate_moves(GenType::Evasions).len(), all.len());
+        }
+
+        if depth == 0 {return}
+        for r#move in all {
+            chess.make_move(r#move);
+            check_gen_types(chess, depth - 1);
+            chess.unmake_move(r#move);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn captures_and_quiets_split_all_moves() {
+        for num in 1..=6 {
+            check_gen_types(&mut Chess::position(num), 2);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use super::*;
+
+impl Chess {
+    // the move follows the rules for its piece, ignoring whether the own king is left in check;
+    // castling is only pseudo legal when it can actually be played
+    pub fn is_pseudo_legal(&self, r#move: Move) -> bool {
+        let colour = self.colour_to_move();
+        let (start, target, flag) = (r#move.start(), r#move.target(), r#move.flag());
+        let piece = self.board(start);
+        if flag > PROMOTE_TO_BISHOP_FLAG || piece == NONE || !piece.is_colour(colour) {return false}
+        let piece_type = piece.get_type();
+
+        if flag == CASTLE_FLAG {
+            return piece_type == KING && [KING_SIDE, QUEEN_SIDE].into_iter().any(|side| self.castle_move(side) == Some(r#move))
+        }
+        if has_square(self.colour_bb(colour), target) {return false}
+        let occupied = self.occupied_bb();
+
+        match piece_type {
+            PAWN => {
+                let forward = if self.is_white_to_move {8} else {-8};
+                let is_capture = piece_attacks(PAWN, colour, start).contains(&target);
+                let is_last_rank = has_square(RANK_1 | RANK_8, target);
+                match flag {
+                    EN_PASSANT_FLAG => is_capture && target == self.en_passant,
+                    DOUBLE_PUSH_FLAG => {
+                        let start_rank = if self.is_white_to_move {1} else {6};
+                        start / 8 == start_rank && target == start + 2 * forward
+                            && !has_square(occupied, start + forward) && !has_square(occupied, target)
+                    }
+                    _ => {
+                        r#move.is_promotion() == is_last_rank && if is_capture {
+                            has_square(self.colour_bb(colour.opponent()), target)
+                        } else {
+                            target == start + forward && !has_square(occupied, target)
+                        }
+                    }
+                }
+            }
+            _ if flag != NO_FLAG => false,
+            KNIGHT | KING => piece_attacks(piece_type, colour, start).contains(&target),
+            _ => has_square(slider_attacks(piece_type, start, occupied), target),
+        }
+    }
+
+    // decides a single move without generating the others
+    pub fn is_legal(&self, r#move: Move) -> bool {
+        if !self.is_pseudo_legal(r#move) {return false}
+        if r#move.flag() == CASTLE_FLAG {return true}
+
+        let (start, target) = (r#move.start(), r#move.target());
+        let mut captured = square_bb(target);
+        if r#move.flag() == EN_PASSANT_FLAG {
+            captured = square_bb(if self.is_white_to_move {target - 8} else {target + 8});
+        }
+        let occupied = self.occupied_bb() & !square_bb(start) & !captured | square_bb(target);
+        let king = if start == self.side[self.colour_index()].king {target} else {self.side[self.colour_index()].king};
+        let opponent = self.colour_to_move().opponent();
+        self.attackers_to_occupied(king, occupied) & self.colour_bb(opponent) & !captured == EMPTY
+    }
+}
+
+#[cfg(test)]
+mod legality_tests {
+    use super::*;
+
+    #[test]
+    fn agrees_with_generation() {
+        let flags = [NO_FLAG, EN_PASSANT_FLAG, CASTLE_FLAG, DOUBLE_PUSH_FLAG, PROMOTE_TO_QUEEN_FLAG, PROMOTE_TO_KNIGHT_FLAG, PROMOTE_TO_ROOK_FLAG, PROMOTE_TO_BISHOP_FLAG];
+        let fens = [
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "8/8/8/KPp4r/8/8/8/6k1 w - c6 0 1",
+            "4k3/8/8/1b6/8/3P4/4K2r/8 w - - 0 1",
+            "r3k2r/8/8/8/8/1q6/8/R3K2R w KQkq - 0 1",
+        ];
+        let positions = (1..=6).map(Chess::position).chain(fens.into_iter().map(Chess::build));
+        for chess in positions {
+            let legal_moves = chess.generate_legal_moves();
+            for start in 0..64 {
+                for target in 0..64 {
+                    for flag in flags {
+                        let r#move = Move::new(start, target, flag);
+                        assert_eq!(chess.is_legal(r#move), legal_moves.contains(&r#move), "{} {}", chess.to_fen(), r#move.to_text());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_moves() {
+        let chess = Chess::start_position();
+        assert!(!chess.is_pseudo_legal(NONE_MOVE));
+        // a black pawn, a blocked bishop and a pawn promoting on the third rank
+        assert!(!chess.is_pseudo_legal(Move::new(52, 44, NO_FLAG)));
+        assert!(!chess.is_pseudo_legal(Move::new(2, 20, NO_FLAG)));
+        assert!(!chess.is_pseudo_legal(Move::new(12, 20, PROMOTE_TO_QUEEN_FLAG)));
+        assert!(chess.is_pseudo_legal(Move::new(12, 28, DOUBLE_PUSH_FLAG)));
+        // pseudo legal but leaving the king in check
+        let pinned = Chess::build("4k3/8/8/1b6/8/3P4/4K3/8 w - - 0 1");
+        assert!(pinned.is_pseudo_legal(Move::new(19, 27, NO_FLAG)));
+        assert!(!pinned.is_legal(Move::new(19, 27, NO_FLAG)));
+    }
+}
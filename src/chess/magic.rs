@@ -0,0 +1,191 @@
+use super::*;
+use std::sync::LazyLock;
+
+const ROOK_TABLE_SIZE: usize = 102400;
+const BISHOP_TABLE_SIZE: usize = 5248;
+
+// fancy magic: attacks = table[offset + ((occupied & mask) * magic) >> shift]
+#[derive(Debug, Clone, Copy)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+const NO_MAGIC: Magic = Magic { mask: EMPTY, magic: 0, shift: 0, offset: 0 };
+
+impl Magic {
+    fn index(self, occupied: Bitboard) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+struct MagicTables {
+    rook_magics: [Magic; 64],
+    bishop_magics: [Magic; 64],
+    rook_attacks: Vec<Bitboard>,
+    bishop_attacks: Vec<Bitboard>,
+}
+// the magic search is too slow for const evaluation, so it runs once on first use
+static MAGICS: LazyLock<MagicTables> = LazyLock::new(precomputed_magics);
+// squares strictly between two squares on the same line, empty otherwise
+pub static BETWEEN: [[Bitboard; 64]; 64] = precomputed_between();
+
+pub fn rook_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    let magics = &*MAGICS;
+    magics.rook_attacks[magics.rook_magics[square as usize].index(occupied)]
+}
+pub fn bishop_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    let magics = &*MAGICS;
+    magics.bishop_attacks[magics.bishop_magics[square as usize].index(occupied)]
+}
+pub fn slider_attacks(piece_type: PieceType, square: Square, occupied: Bitboard) -> Bitboard {
+    match piece_type {
+        BISHOP => bishop_attacks(square, occupied),
+        ROOK => rook_attacks(square, occupied),
+        QUEEN => bishop_attacks(square, occupied) | rook_attacks(square, occupied),
+        _ => unreachable!(),
+    }
+}
+pub fn between(start: Square, target: Square) -> Bitboard {
+    BETWEEN[start as usize][target as usize]
+}
+
+// reference ray walk, used only to fill the tables
+fn sliding_attacks_slow(square: Square, occupied: Bitboard, dir_indices: std::ops::Range<usize>) -> Bitboard {
+    let mut attacks = EMPTY;
+    for dir_index in dir_indices {
+        let dist_edge = NUM_SQUARES_TO_EDGES[square as usize][dir_index];
+        for n in 0..dist_edge {
+            let target = square + DIRECTION_OFFSETS[dir_index] * (n + 1) as i8;
+            attacks |= square_bb(target);
+            if has_square(occupied, target) {break}
+        }
+    }
+    attacks
+}
+
+// xorshift64*, fixed seed so the magics are the same on every run
+struct Prng(u64);
+impl Prng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(2685821657736338717)
+    }
+    fn sparse(&mut self) -> u64 { self.next() & self.next() & self.next() }
+}
+
+// one seed per rank, picked because they find the magics in few tries
+const MAGIC_SEEDS: [u64; 8] = [728, 10316, 55013, 32803, 12281, 15100, 16645, 255];
+
+fn find_magics(dir_indices: std::ops::Range<usize>, magics: &mut [Magic; 64], table: &mut [Bitboard]) {
+    let mut occupancies = Vec::with_capacity(4096);
+    let mut references = Vec::with_capacity(4096);
+    let mut epochs = vec![0u32; 4096];
+    let mut epoch = 0;
+    let mut offset = 0;
+
+    for square in 0..64 {
+        let rank_edges = (RANK_1 | RANK_8) & !(RANK_1 << (8 * (square / 8)));
+        let file_edges = (FILE_A | FILE_H) & !(FILE_A << (square % 8));
+        let mask = sliding_attacks_slow(square, EMPTY, dir_indices.clone()) & !(rank_edges | file_edges);
+        let bits = mask.count_ones();
+
+        // carry-rippler: every subset of the mask
+        occupancies.clear();
+        references.clear();
+        let mut occupied = EMPTY;
+        loop {
+            occupancies.push(occupied);
+            references.push(sliding_attacks_slow(square, occupied, dir_indices.clone()));
+            occupied = occupied.wrapping_sub(mask) & mask;
+            if occupied == EMPTY {break}
+        }
+
+        let mut prng = Prng(MAGIC_SEEDS[(square / 8) as usize]);
+        let mut magic = Magic { mask, magic: 0, shift: 64 - bits, offset };
+        'search: loop {
+            magic.magic = prng.sparse();
+            if (mask.wrapping_mul(magic.magic) >> 56).count_ones() < 6 {continue}
+            epoch += 1;
+            for (&occupied, &reference) in occupancies.iter().zip(&references) {
+                let index = magic.index(occupied);
+                if epochs[index - offset] < epoch {
+                    epochs[index - offset] = epoch;
+                    table[index] = reference;
+                } else if table[index] != reference {
+                    continue 'search;
+                }
+            }
+            break;
+        }
+        magics[square as usize] = magic;
+        offset += 1 << bits;
+    }
+    assert_eq!(offset, table.len());
+}
+
+fn precomputed_magics() -> MagicTables {
+    let mut rook_magics = [NO_MAGIC; 64];
+    let mut rook_attacks = vec![EMPTY; ROOK_TABLE_SIZE];
+    find_magics(0..4, &mut rook_magics, &mut rook_attacks);
+
+    let mut bishop_magics = [NO_MAGIC; 64];
+    let mut bishop_attacks = vec![EMPTY; BISHOP_TABLE_SIZE];
+    find_magics(4..8, &mut bishop_magics, &mut bishop_attacks);
+
+    MagicTables { rook_magics, bishop_magics, rook_attacks, bishop_attacks }
+}
+
+const fn precomputed_between() -> [[Bitboard; 64]; 64] {
+    let mut between = [[EMPTY; 64]; 64];
+    let mut start = 0;
+    while start < 64 {
+        let mut dir_index = 0;
+        while dir_index < 8 {
+            let dist_edge = NUM_SQUARES_TO_EDGES[start][dir_index];
+            let mut squares = EMPTY;
+            let mut n = 0;
+            while n < dist_edge {
+                let target = start as Square + DIRECTION_OFFSETS[dir_index] * (n + 1) as i8;
+                between[start][target as usize] = squares;
+                squares |= 1 << target;
+                n += 1;
+            }
+            dir_index += 1;
+        }
+        start += 1;
+    }
+    between
+}
+
+#[cfg(test)]
+mod magic_tests {
+    use super::*;
+
+    #[test]
+    fn magic_matches_ray_walk() {
+        let mut prng = Prng(1070372);
+        for square in 0..64 {
+            for _ in 0..200 {
+                let occupied = prng.sparse() | prng.sparse();
+                assert_eq!(rook_attacks(square, occupied), sliding_attacks_slow(square, occupied, 0..4));
+                assert_eq!(bishop_attacks(square, occupied), sliding_attacks_slow(square, occupied, 4..8));
+            }
+        }
+    }
+
+    #[test]
+    fn between_squares() {
+        assert_eq!(between(0, 63), squares_bb(&[9, 18, 27, 36, 45, 54]));
+        assert_eq!(between(4, 7), squares_bb(&[5, 6]));
+        assert_eq!(between(4, 5), EMPTY);
+        assert_eq!(between(1, 18), EMPTY);
+    }
+
+    fn squares_bb(squares: &[Square]) -> Bitboard {
+        squares.iter().fold(EMPTY, |bitboard, &square| bitboard | square_bb(square))
+    }
+}
@@ -60,19 +60,39 @@ impl Move {
             return Move::new(start, target, Move::promotion_flag(promotion_type));
         }
         else if chess.board[start as usize].is_type(KING) {
-            if target as i8 - start as i8 == 2
-            || target as i8 - start as i8 == -2
+            // the king takes its own rook in Chess960 notation
+            let king = chess.board[start as usize];
+            if chess.board[target as usize] == Piece::new(ROOK, king.get_colour()) {
+                let back_rank = start - start % 8;
+                return Move::new(start, back_rank + if target > start {6} else {2}, CASTLE_FLAG)
+            }
+            if (target - start).abs() == 2 && (target % 8 == 6 || target % 8 == 2)
             {return Move::new(start, target, CASTLE_FLAG)}
         }
         else if chess.board[start as usize].is_type(PAWN) {
             if target as i8 - start as i8 == 16
             || target as i8 - start as i8 == -16
             {return Move::new(start, target, DOUBLE_PUSH_FLAG)}
+            if target == chess.en_passant && (target - start) % 8 != 0
+            {return Move::new(start, target, EN_PASSANT_FLAG)}
         }
         
         return Move::new(start, target, NO_FLAG);
     }
 
+    // long algebraic notation of a legal move, None for anything else
+    pub fn parse_legal(chess: &Chess, text: &str) -> Option<Move> {
+        let chars: Vec<char> = text.chars().collect();
+        let is_square = |file: char, rank: char| ('a'..='h').contains(&file) && ('1'..='8').contains(&rank);
+        let well_formed = match chars[..] {
+            [a, b, c, d] => is_square(a, b) && is_square(c, d),
+            [a, b, c, d, promotion] => is_square(a, b) && is_square(c, d) && "nbrq".contains(promotion),
+            _ => false,
+        };
+        if !well_formed {return None}
+        Some(Move::from_text(chess, text)).filter(|&r#move| chess.is_legal(r#move))
+    }
+
     pub fn to_text(self) -> String {
         let (start, target) = (self.start(), self.target());
         match self.flag() {
@@ -83,5 +103,61 @@ impl Move {
             _ => format!("{}{}{}", square_to_text(start), square_to_text(target), self.promotion_type().symbol()),
         }
     }
+    // UCI_Chess960 writes castling as the king taking its own rook
+    pub fn to_text_960(self, chess: &Chess) -> String {
+        if self.flag() != CASTLE_FLAG {return self.to_text()}
+        let (king, rook, _, _) = chess.castle_squares(chess.colour_index(), self);
+        format!("{}{}", square_to_text(king), square_to_text(rook))
+    }
+
+}
+
+#[cfg(test)]
+mod move_tests {
+    use super::*;
+
+    #[test]
+    fn castling_text() {
+        let kiwipete = Chess::position(2);
+        assert_eq!(Move::from_text(&kiwipete, "e1g1"), Move::new(4, 6, CASTLE_FLAG));
+        assert_eq!(Move::from_text(&kiwipete, "e1h1"), Move::new(4, 6, CASTLE_FLAG));
+        assert_eq!(Move::from_text(&kiwipete, "e1a1"), Move::new(4, 2, CASTLE_FLAG));
+        assert_eq!(Move::new(4, 2, CASTLE_FLAG).to_text_960(&kiwipete), "e1a1");
+
+        // the king already on g1 and a rook on b1
+        let chess960 = Chess::build("1r4kr/8/8/8/8/8/8/1R4KR w BHbh - 0 1");
+        assert_eq!(Move::from_text(&chess960, "g1h1"), Move::new(6, 6, CASTLE_FLAG));
+        assert_eq!(Move::from_text(&chess960, "g1b1"), Move::new(6, 2, CASTLE_FLAG));
+        assert_eq!(Move::from_text(&chess960, "g1f1"), Move::new(6, 5, NO_FLAG));
+        for r#move in [Move::new(6, 6, CASTLE_FLAG), Move::new(6, 2, CASTLE_FLAG)] {
+            assert!(chess960.generate_legal_moves().contains(&r#move));
+            assert_eq!(Move::from_text(&chess960, &r#move.to_text_960(&chess960)), r#move);
+        }
+    }
 
+    #[test]
+    fn chess960_castling() {
+        let mut chess = Chess::build("1r4kr/8/8/8/8/8/8/1R4KR w BHbh - 0 1");
+        chess.make_move(Move::new(6, 2, CASTLE_FLAG));
+        assert_eq!(chess.to_fen(), "1r4kr/8/8/8/8/8/8/2KR3R b kq - 1 1");
+        chess.make_move(Move::new(62, 62, CASTLE_FLAG));
+        assert_eq!(chess.to_fen(), "1r3rk1/8/8/8/8/8/8/2KR3R w - - 2 2");
+        chess.unmake_move(Move::new(62, 62, CASTLE_FLAG));
+        chess.unmake_move(Move::new(6, 2, CASTLE_FLAG));
+        assert_eq!(chess.to_fen(), "1r4kr/8/8/8/8/8/8/1R4KR w KQkq - 0 1");
+        // the b1 rook hides the a1 rook from c1
+        let shielded = Chess::build("4k3/8/8/8/8/8/8/rRK5 w B - 0 1");
+        assert!(!shielded.generate_legal_moves().contains(&Move::new(2, 2, CASTLE_FLAG)));
+    }
+
+    #[test]
+    fn parse_legal() {
+        let en_passant = Chess::build("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        assert_eq!(Move::parse_legal(&en_passant, "e5f6"), Some(Move::new(36, 45, EN_PASSANT_FLAG)));
+        assert_eq!(Move::parse_legal(&en_passant, "e5d6"), None);
+        assert_eq!(Move::parse_legal(&en_passant, "e1e2"), Some(Move::new(4, 12, NO_FLAG)));
+        for malformed in ["", "e1", "e1e9", "i1e2", "e7e8k", "e1e2 "] {
+            assert_eq!(Move::parse_legal(&en_passant, malformed), None);
+        }
+    }
 }
@@ -0,0 +1,181 @@
+use std::ops::Deref;
+
+use super::*;
+
+// the most moves a legal position can have
+pub const MAX_MOVES: usize = 218;
+
+// stack allocated list of moves, each move with a score slot used for ordering
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    scores: [i32; MAX_MOVES],
+    len: usize,
+}
+
+impl Default for MoveList {
+    fn default() -> Self { MoveList::new() }
+}
+
+impl MoveList {
+    pub fn new() -> MoveList {
+        MoveList { moves: [NONE_MOVE; MAX_MOVES], scores: [0; MAX_MOVES], len: 0 }
+    }
+
+    pub fn push(&mut self, r#move: Move) {
+        debug_assert!(self.len < MAX_MOVES, "more than {MAX_MOVES} moves");
+        self.moves[self.len] = r#move;
+        self.scores[self.len] = 0;
+        self.len += 1;
+    }
+    pub fn clear(&mut self) { self.len = 0 }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Move) -> bool) {
+        let mut len = 0;
+        for index in 0..self.len {
+            if keep(&self.moves[index]) {
+                self.moves[len] = self.moves[index];
+                self.scores[len] = self.scores[index];
+                len += 1;
+            }
+        }
+        self.len = len;
+    }
+
+    pub fn score(&self, index: usize) -> i32 { self.scores[index] }
+    pub fn scores(&self) -> &[i32] { &self.scores[..self.len] }
+    pub fn scores_mut(&mut self) -> &mut [i32] { &mut self.scores[..self.len] }
+
+    pub fn score_moves(&mut self, mut score: impl FnMut(Move) -> i32) {
+        for index in 0..self.len {
+            self.scores[index] = score(self.moves[index]);
+        }
+    }
+    // highest score first, moves with the same score keep their order
+    pub fn sort_by_score(&mut self) {
+        for index in 1..self.len {
+            let (r#move, score) = (self.moves[index], self.scores[index]);
+            let mut hole = index;
+            while hole > 0 && self.scores[hole - 1] < score {
+                self.moves[hole] = self.moves[hole - 1];
+                self.scores[hole] = self.scores[hole - 1];
+                hole -= 1;
+            }
+            self.moves[hole] = r#move;
+            self.scores[hole] = score;
+        }
+    }
+    // stable, each move keeps its score
+    pub fn sort_by_key<K: Ord>(&mut self, mut key: impl FnMut(&Move) -> K) {
+        for index in 1..self.len {
+            let (r#move, score) = (self.moves[index], self.scores[index]);
+            let move_key = key(&r#move);
+            let mut hole = index;
+            while hole > 0 && key(&self.moves[hole - 1]) > move_key {
+                self.moves[hole] = self.moves[hole - 1];
+                self.scores[hole] = self.scores[hole - 1];
+                hole -= 1;
+            }
+            self.moves[hole] = r#move;
+            self.scores[hole] = score;
+        }
+    }
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.moves[..self.len].swap(a, b);
+        self.scores[..self.len].swap(a, b);
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+    fn deref(&self) -> &[Move] { &self.moves[..self.len] }
+}
+
+impl std::fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+impl PartialEq for MoveList {
+    fn eq(&self, other: &Self) -> bool { **self == **other }
+}
+
+impl Extend<Move> for MoveList {
+    fn extend<T: IntoIterator<Item = Move>>(&mut self, iter: T) {
+        for r#move in iter {
+            self.push(r#move);
+        }
+    }
+}
+impl FromIterator<Move> for MoveList {
+    fn from_iter<T: IntoIterator<Item = Move>>(iter: T) -> Self {
+        let mut moves = MoveList::new();
+        moves.extend(iter);
+        moves
+    }
+}
+
+pub struct IntoIter {
+    list: MoveList,
+    index: usize,
+}
+impl Iterator for IntoIter {
+    type Item = Move;
+    fn next(&mut self) -> Option<Move> {
+        let r#move = self.list.get(self.index).copied();
+        self.index += 1;
+        r#move
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.list.len.saturating_sub(self.index);
+        (len, Some(len))
+    }
+}
+impl ExactSizeIterator for IntoIter {}
+
+impl IntoIterator for MoveList {
+    type Item = Move;
+    type IntoIter = IntoIter;
+    fn into_iter(self) -> IntoIter { IntoIter { list: self, index: 0 } }
+}
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+#[cfg(test)]
+mod move_list_tests {
+    use super::*;
+
+    #[test]
+    fn sort_by_score_is_stable() {
+        let mut moves: MoveList = (0..6).map(|n| Move::new(n, n + 8, NO_FLAG)).collect();
+        moves.score_moves(|r#move| if r#move.start() % 2 == 0 {1} else {5});
+        moves.sort_by_score();
+        assert_eq!(moves.iter().map(|r#move| r#move.start()).collect::<Vec<_>>(), vec![1, 3, 5, 0, 2, 4]);
+        assert_eq!(moves.scores(), &[5, 5, 5, 1, 1, 1]);
+    }
+
+    #[test]
+    fn swap_and_sort_by_key_keep_scores() {
+        let mut moves: MoveList = (0..6).map(|n| Move::new(n, n + 8, NO_FLAG)).collect();
+        moves.score_moves(|r#move| r#move.start() as i32 * 10);
+        moves.swap(0, 5);
+        assert_eq!(moves.iter().map(|r#move| r#move.start()).collect::<Vec<_>>(), vec![5, 1, 2, 3, 4, 0]);
+        assert_eq!(moves.scores(), &[50, 10, 20, 30, 40, 0]);
+        moves.sort_by_key(|r#move| r#move.start() % 3);
+        assert_eq!(moves.iter().map(|r#move| r#move.start()).collect::<Vec<_>>(), vec![3, 0, 1, 4, 5, 2]);
+        assert_eq!(moves.scores(), &[30, 0, 10, 40, 50, 20]);
+    }
+
+    #[test]
+    fn retain_keeps_scores() {
+        let mut moves: MoveList = (0..6).map(|n| Move::new(n, n + 8, NO_FLAG)).collect();
+        moves.score_moves(|r#move| r#move.start() as i32 * 10);
+        moves.retain(|r#move| r#move.start() >= 3);
+        assert_eq!(moves.len(), 3);
+        assert_eq!(moves.scores(), &[30, 40, 50]);
+        assert_eq!(moves.into_iter().map(|r#move| r#move.start()).collect::<Vec<_>>(), vec![3, 4, 5]);
+    }
+}
@@ -3,32 +3,37 @@ use super::*;
 use std::time::*;
 
 
-static mut GEN_MOVE_TIME: Duration = Duration::ZERO;
-static mut MAKE_MOVE_TIME: Duration = Duration::ZERO;
-static mut UNMAKE_MOVE_TIME: Duration = Duration::ZERO;
+// time spent generating, making and unmaking moves
+#[derive(Default)]
+struct PerftTimes {
+    gen_move: Duration,
+    make_move: Duration,
+    unmake_move: Duration,
+}
 
 impl Chess {
     pub fn time_perft(&mut self, depth: u16 /* assuming >= 1 */) {
-        unsafe {
-            GEN_MOVE_TIME = Duration::ZERO;
-            MAKE_MOVE_TIME = Duration::ZERO;
-            UNMAKE_MOVE_TIME = Duration::ZERO;
-            let _ = self.perft(depth);
-            let tot = GEN_MOVE_TIME + MAKE_MOVE_TIME + UNMAKE_MOVE_TIME;
-            println!("tot= {tot:?}\ngeneration moves= {:.2?}%\nmake move= {:.2?}%\nunmake move= {:.2?}%",
-                100.0*GEN_MOVE_TIME.as_secs_f64()/tot.as_secs_f64(),
-                100.0*MAKE_MOVE_TIME.as_secs_f64()/tot.as_secs_f64(),
-                100.0*UNMAKE_MOVE_TIME.as_secs_f64()/tot.as_secs_f64(),
-            );
-        }
+        let mut times = PerftTimes::default();
+        let _ = self.perft_timed(depth, &mut times);
+        let tot = times.gen_move + times.make_move + times.unmake_move;
+        println!("attack maps= {}", if self.has_attack_maps() {"on"} else {"off"});
+        println!("tot= {tot:?}\ngeneration moves= {:.2?}%\nmake move= {:.2?}%\nunmake move= {:.2?}%",
+            100.0*times.gen_move.as_secs_f64()/tot.as_secs_f64(),
+            100.0*times.make_move.as_secs_f64()/tot.as_secs_f64(),
+            100.0*times.unmake_move.as_secs_f64()/tot.as_secs_f64(),
+        );
     }
 
     pub fn perft(&mut self, depth: u16) -> u64 {
+        self.perft_timed(depth, &mut PerftTimes::default())
+    }
+
+    fn perft_timed(&mut self, depth: u16, times: &mut PerftTimes) -> u64 {
         let mut nodes: u64 = 0;
 
         let t_start = Instant::now();
         let legal_moves = self.generate_legal_moves();
-        unsafe {GEN_MOVE_TIME += Instant::now() - t_start}
+        times.gen_move += Instant::now() - t_start;
 
         if depth == 0 {return 1}
         
@@ -36,14 +41,14 @@ impl Chess {
             //println!("make {}", r#move.to_text());
             let t_start = Instant::now();
             self.make_move(r#move);
-            unsafe {MAKE_MOVE_TIME += Instant::now() - t_start}
+            times.make_move += Instant::now() - t_start;
 
-            nodes += self.perft(depth - 1);
+            nodes += self.perft_timed(depth - 1, times);
             
             //println!("unmake {}", r#move.to_text());
             let t_start = Instant::now();
             self.unmake_move(r#move);
-            unsafe {UNMAKE_MOVE_TIME += Instant::now() - t_start}
+            times.unmake_move += Instant::now() - t_start;
         }
         return nodes;
     }
@@ -63,6 +68,147 @@ impl Chess {
         }
         return nodes;
     }
+
+    // nodes under each root move
+    pub fn perft_divide(&mut self, depth: u16) -> Vec<(Move, u64)> {
+        assert!(depth >= 1);
+        self.generate_legal_moves().into_iter().map(|r#move| {
+            if depth == 1 {return (r#move, 1)}
+            self.make_move(r#move);
+            let nodes = self.perft_bulck(depth - 1);
+            self.unmake_move(r#move);
+            (r#move, nodes)
+        }).collect()
+    }
+
+    // positions reached by transposition are counted once per depth
+    pub fn perft_hashed(&mut self, depth: u16, table: &mut PerftTable) -> u64 {
+        assert!(depth >= 1);
+        let legal_moves = self.generate_legal_moves();
+        if depth == 1 {return legal_moves.len() as u64}
+
+        let hash = self.hash();
+        let index = (hash % table.entries.len() as u64) as usize;
+        let (entry_hash, entry_depth, entry_nodes) = table.entries[index];
+        if entry_hash == hash && entry_depth == depth {return entry_nodes}
+
+        let mut nodes = 0;
+        for r#move in legal_moves {
+            self.make_move(r#move);
+            nodes += self.perft_hashed(depth - 1, table);
+            self.unmake_move(r#move);
+        }
+        table.entries[index] = (hash, depth, nodes);
+        nodes
+    }
+
+    // the root moves are shared out between the threads, each searching its own copy of the position
+    pub fn perft_parallel(&self, depth: u16, threads: usize) -> u64 {
+        assert!(depth >= 1);
+        let legal_moves = self.generate_legal_moves();
+        if depth == 1 {return legal_moves.len() as u64}
+
+        let threads = threads.clamp(1, legal_moves.len().max(1));
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads).map(|thread| {
+                let mut chess = self.without_attack_maps();
+                let legal_moves = &legal_moves;
+                scope.spawn(move || legal_moves.iter().skip(thread).step_by(threads).map(|&r#move| {
+                    chess.make_move(r#move);
+                    let nodes = chess.perft_bulck(depth - 1);
+                    chess.unmake_move(r#move);
+                    nodes
+                }).sum::<u64>())
+            }).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).sum()
+        })
+    }
+}
+
+// always-replace cache of (hash, depth, nodes)
+pub struct PerftTable {
+    entries: Vec<(Hash, u16, u64)>,
+}
+impl PerftTable {
+    pub fn new(size: usize) -> PerftTable {
+        PerftTable { entries: vec![(0, 0, 0); size.max(1)] }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PerftSuiteError {
+    Malformed(usize, String),
+    Fen(usize, FenError),
+}
+
+impl std::fmt::Display for PerftSuiteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PerftSuiteError::Malformed(line, text) => write!(f, "line {line}: malformed entry \"{text}\""),
+            PerftSuiteError::Fen(line, error) => write!(f, "line {line}: {error}"),
+        }
+    }
+}
+impl std::error::Error for PerftSuiteError {}
+
+// a position of an EPD perft suite with the expected node counts by depth
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerftCase {
+    pub fen: String,
+    pub depths: Vec<(u16, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerftMismatch {
+    pub fen: String,
+    pub depth: u16,
+    pub expected: u64,
+    pub found: u64,
+}
+
+impl std::fmt::Display for PerftMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} depth {}: expected {} found {}", self.fen, self.depth, self.expected, self.found)
+    }
+}
+
+// lines like "<fen> ;D1 20 ;D2 400", blank lines and '#' comments are skipped
+pub fn parse_perft_suite(text: &str) -> Result<Vec<PerftCase>, PerftSuiteError> {
+    let mut cases = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let (number, line) = (index + 1, line.trim());
+        if line.is_empty() || line.starts_with('#') {continue}
+        let malformed = || PerftSuiteError::Malformed(number, line.to_string());
+
+        let mut fields = line.split(';');
+        let fen = fields.next().unwrap().trim().to_string();
+        Chess::from_fen(&fen).map_err(|error| PerftSuiteError::Fen(number, error))?;
+        let mut depths = Vec::new();
+        for field in fields {
+            let (depth, nodes) = field.trim().split_once(char::is_whitespace).ok_or_else(malformed)?;
+            let depth = depth.strip_prefix('D').and_then(|depth| depth.parse().ok()).filter(|&depth| depth >= 1).ok_or_else(malformed)?;
+            let nodes = nodes.trim().parse().map_err(|_| malformed())?;
+            depths.push((depth, nodes));
+        }
+        if depths.is_empty() {return Err(malformed())}
+        cases.push(PerftCase { fen, depths });
+    }
+    Ok(cases)
+}
+
+// checks every depth up to max_depth, the mismatches are returned in the order of the suite
+pub fn run_perft_suite(cases: &[PerftCase], max_depth: u16, threads: usize) -> Vec<PerftMismatch> {
+    let mut mismatches = Vec::new();
+    for case in cases {
+        let chess = Chess::build(&case.fen);
+        for &(depth, expected) in case.depths.iter().filter(|&&(depth, _)| depth >= 1 && depth <= max_depth) {
+            let found = chess.perft_parallel(depth, threads);
+            if found != expected {
+                mismatches.push(PerftMismatch { fen: case.fen.clone(), depth, expected, found });
+            }
+        }
+    }
+    mismatches
 }
 
 #[cfg(test)]
@@ -137,4 +283,74 @@ mod perft_tests {
             &[46, 2079, 89890, 3894594, 164075551],
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn threads() {
+        let handles: Vec<_> = (1..=4).map(|num| std::thread::spawn(move || Chess::position(num).perft_bulck(3))).collect();
+        let nodes: Vec<u64> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        assert_eq!(nodes, [8902, 97862, 2812, 9467]);
+    }
+
+    #[test]
+    fn divide_hashed_and_parallel() {
+        let mut chess = Chess::position(2);
+        let divide = chess.perft_divide(3);
+        assert_eq!(divide.len(), 48);
+        assert_eq!(divide.iter().map(|&(_, nodes)| nodes).sum::<u64>(), 97862);
+        assert!(divide.contains(&(Move::new(4, 6, CASTLE_FLAG), 2059)));
+
+        let mut table = PerftTable::new(1 << 16);
+        assert_eq!(chess.perft_hashed(4, &mut table), 4085603);
+        assert_eq!(chess.perft_hashed(4, &mut table), 4085603);
+        assert_eq!(chess.perft_parallel(3, 4), 97862);
+        assert_eq!(chess.to_fen(), Chess::position(2).to_fen());
+    }
+
+    #[test]
+    fn suite() {
+        let cases = parse_perft_suite(include_str!("../../perftsuite.epd")).unwrap();
+        assert_eq!(cases.len(), 6);
+        assert_eq!(run_perft_suite(&cases, 3, 2), []);
+
+        let wrong = parse_perft_suite("# comment\n\n4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1 15 ;D2 60").unwrap();
+        assert_eq!(run_perft_suite(&wrong, 2, 1), [PerftMismatch { fen: "4k3/8/8/8/8/8/8/4K2R w K - 0 1".to_string(), depth: 2, expected: 60, found: 66 }]);
+        assert_eq!(parse_perft_suite("4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1"), Err(PerftSuiteError::Malformed(1, "4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1".to_string())));
+        assert_eq!(parse_perft_suite("4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D0 1"), Err(PerftSuiteError::Malformed(1, "4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D0 1".to_string())));
+        assert!(matches!(parse_perft_suite("\n4k3/8/8 w - - ;D1 1"), Err(PerftSuiteError::Fen(2, _))));
+    }
+
+    #[test]
+    fn without_attack_maps() {
+        for (num, depth, nodes) in [(1, 4, 197281), (2, 3, 97862), (3, 4, 43238), (4, 4, 422333), (5, 3, 62379), (6, 3, 89890)] {
+            let mut chess = Chess::position(num).without_attack_maps();
+            assert_eq!(chess.perft_bulck(depth), nodes, "{num}");
+            assert!(chess.side.iter().all(|side| side.attacks == [0; 64]));
+        }
+        walk_test_positions(2, |chess| chess.set_attack_maps(false), |chess| assert_eq!(compare_generators(chess), None));
+
+        // the maps come back as if they had been kept up to date
+        let mut chess = Chess::position(2).without_attack_maps();
+        for text in ["e1g1", "h3g2", "e5f7", "e8c8"] {
+            chess.make_move(Move::from_text(&chess, text));
+        }
+        chess.set_attack_maps(true);
+        let fresh = Chess::build(&chess.to_fen());
+        for (side, fresh_side) in chess.side.iter().zip(&fresh.side) {
+            assert_eq!(side.attacks, fresh_side.attacks);
+            assert_eq!(side.piece_attacks, fresh_side.piece_attacks);
+        }
+    }
+
+    #[test]
+    fn chess960() {
+        for (fen, nodes) in [
+            ("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", [21, 528, 12189, 326672]),
+            ("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", [21, 807, 18002, 667366]),
+            ("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", [20, 479, 10471, 273318]),
+            ("qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9", [22, 593, 13440, 382958]),
+            ("1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9", [28, 1120, 31058, 1171749]),
+        ] {
+            perft_test(Chess::build(fen), &nodes);
+        }
+    }
+}
@@ -37,9 +37,12 @@ impl Piece {
 }
 impl PieceType {
     pub fn from_symbol(symbol: char) -> PieceType {
+        PieceType::try_from_symbol(symbol).unwrap()
+    }
+    pub fn try_from_symbol(symbol: char) -> Option<PieceType> {
         match symbol.to_ascii_lowercase() {
-            'p' => PAWN, 'n' => KNIGHT, 'b' => BISHOP,
-            'r' => ROOK, 'q' => QUEEN, 'k' => KING, _ => unreachable!(),
+            'p' => Some(PAWN), 'n' => Some(KNIGHT), 'b' => Some(BISHOP),
+            'r' => Some(ROOK), 'q' => Some(QUEEN), 'k' => Some(KING), _ => None,
         }
     }
     pub fn symbol(self) -> char {
@@ -0,0 +1,125 @@
+use std::ops::Deref;
+
+use super::*;
+
+// the most pieces of one type a side can have, the two it starts with and its eight pawns promoted
+pub const MAX_PIECES: usize = 10;
+
+// stack allocated squares of a side's pieces of one type, in no particular order
+#[derive(Clone, Copy)]
+pub struct PieceList {
+    squares: [Square; MAX_PIECES],
+    len: u8,
+}
+
+impl Default for PieceList {
+    fn default() -> Self { PieceList::new() }
+}
+
+impl PieceList {
+    pub const fn new() -> PieceList {
+        PieceList { squares: [0; MAX_PIECES], len: 0 }
+    }
+
+    // #return: the index of the square
+    fn push(&mut self, square: Square) -> usize {
+        self.squares[self.len as usize] = square;
+        self.len += 1;
+        self.len as usize - 1
+    }
+    // the last square fills the hole, #return: the square now at the index if any
+    fn swap_remove(&mut self, index: usize) -> Option<Square> {
+        self.len -= 1;
+        self.squares[index] = self.squares[self.len as usize];
+        (index < self.len as usize).then_some(self.squares[index])
+    }
+}
+
+impl SideState {
+    fn list_mut(&mut self, piece_type: PieceType) -> &mut PieceList {
+        match piece_type {
+            PAWN => &mut self.pawns,
+            KNIGHT => &mut self.knights,
+            BISHOP => &mut self.bishops,
+            ROOK => &mut self.rooks,
+            QUEEN => &mut self.queens,
+            _ => unreachable!(),
+        }
+    }
+    pub(super) fn add_to_list(&mut self, piece_type: PieceType, square: Square) {
+        let index = self.list_mut(piece_type).push(square);
+        self.list_index[square as usize] = index as u8;
+    }
+    pub(super) fn remove_from_list(&mut self, piece_type: PieceType, square: Square) {
+        let index = self.list_index[square as usize];
+        if let Some(moved) = self.list_mut(piece_type).swap_remove(index as usize) {
+            self.list_index[moved as usize] = index;
+        }
+        self.list_index[square as usize] = 0;
+    }
+    pub(super) fn move_in_list(&mut self, piece_type: PieceType, start: Square, target: Square) {
+        let index = self.list_index[start as usize];
+        self.list_mut(piece_type).squares[index as usize] = target;
+        self.list_index[target as usize] = index;
+        self.list_index[start as usize] = 0;
+    }
+}
+
+impl Deref for PieceList {
+    type Target = [Square];
+    fn deref(&self) -> &[Square] { &self.squares[..self.len as usize] }
+}
+
+impl std::fmt::Debug for PieceList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+impl PartialEq for PieceList {
+    fn eq(&self, other: &Self) -> bool { **self == **other }
+}
+
+impl<'a> IntoIterator for &'a PieceList {
+    type Item = &'a Square;
+    type IntoIter = std::slice::Iter<'a, Square>;
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+#[cfg(test)]
+mod piece_list_tests {
+    use super::*;
+
+    fn assert_lists_match_board(chess: &Chess) {
+        for (colour_index, side) in chess.side.iter().enumerate() {
+            for piece_type in [PAWN, KNIGHT, BISHOP, ROOK, QUEEN] {
+                let list = side.pieces(piece_type);
+                assert_eq!(list.len() as u32, side.bitboards[piece_type.piece_index()].count_ones());
+                for (index, &square) in list.iter().enumerate() {
+                    assert_eq!(chess.board(square), Piece::new(piece_type, Colour::new(colour_index == 0)));
+                    assert_eq!(side.list_index[square as usize] as usize, index);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn follows_the_moves() {
+        walk_test_positions(2, |_| (), |chess| assert_lists_match_board(chess));
+    }
+
+    #[test]
+    fn full_lists() {
+        let chess = Chess::build("k7/8/8/8/8/8/NNNNNNNN/K1N1N3 w - - 0 1");
+        assert_eq!(chess.side[WHITE.colour_index()].knights.len(), MAX_PIECES);
+        assert_eq!(Chess::from_fen("k7/8/8/8/8/8/NNNNNNNN/K1N1N1N1 w - - 0 1"), Err(FenError::TooManyPieces('N')));
+        // each promotion could add one more knight
+        assert_eq!(Chess::from_fen("k7/P7/8/8/8/8/NNNNNNNN/K1N1N3 w - - 0 1"), Err(FenError::TooManyPieces('N')));
+        // every type fits on its own, together they need more promotions than there are pawns
+        assert_eq!(Chess::from_fen("QQQQQQQQ/QQRRRRRR/RRRRBBBB/BBBBBBNN/NNNNNNNN/8/8/k6K w - - 0 1"), Err(FenError::TooManyPieces('B')));
+        assert_eq!(Chess::from_fen("k7/8/8/8/8/QQ6/PPPPPPPP/K7 w - - 0 1"), Err(FenError::TooManyPieces('Q')));
+        assert_eq!(Chess::from_fen("k7/8/8/8/8/P7/PPPPPPPP/K7 w - - 0 1"), Err(FenError::TooManyPieces('P')));
+        // the most moves a legal position can have
+        let most_moves = Chess::build("3Q4/1Q4Q1/4Q3/2Q4R/Q4Q2/3Q4/1Q4Rp/1K1BBNNk w - - 0 1");
+        assert_eq!(most_moves.generate_legal_moves().len(), MAX_MOVES);
+    }
+}
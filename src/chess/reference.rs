@@ -0,0 +1,194 @@
+use super::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+// slow move generator kept as simple as possible to check generate_legal_moves against:
+// every move that follows the piece rules is made and kept if it doesn't leave the king attacked
+impl Chess {
+    pub fn generate_reference_moves(&mut self) -> MoveList {
+        let colour = self.colour_to_move();
+        let mut legal_moves = MoveList::new();
+        for r#move in self.generate_pseudo_legal_moves() {
+            self.make_move(r#move);
+            if !self.is_square_attacked(self.side[colour.colour_index()].king, colour.opponent()) {
+                legal_moves.push(r#move);
+            }
+            self.unmake_move(r#move);
+        }
+        legal_moves
+    }
+
+    fn generate_pseudo_legal_moves(&self) -> MoveList {
+        let colour = self.colour_to_move();
+        let own = self.colour_bb(colour);
+        let mut moves = MoveList::new();
+        for start in squares(own) {
+            let piece_type = self.board(start).get_type();
+            match piece_type {
+                PAWN => self.push_reference_pawn_moves(&mut moves, start),
+                KNIGHT | KING => for &target in piece_attacks(piece_type, colour, start) {
+                    if !has_square(own, target) {moves.push(Move::new(start, target, NO_FLAG))}
+                }
+                _ => for target in squares(slider_attacks(piece_type, start, self.occupied_bb()) & !own) {
+                    moves.push(Move::new(start, target, NO_FLAG));
+                }
+            }
+        }
+        for side in [KING_SIDE, QUEEN_SIDE] {
+            if self.castling & CASTLE_RIGHTS[self.colour_index()][side] == 0 {continue}
+            let king = self.side[self.colour_index()].king;
+            let rook = self.castling_rook(colour, side);
+            let back_rank = king - king % 8;
+            let (king_target, rook_target) = if side == KING_SIDE {(back_rank + 6, back_rank + 5)} else {(back_rank + 2, back_rank + 3)};
+            let span = |a: Square, b: Square| between(a, b) | square_bb(a) | square_bb(b);
+            let blockers = self.occupied_bb() & !square_bb(king) & !square_bb(rook);
+            if (span(king, king_target) | span(rook, rook_target)) & blockers != EMPTY {continue}
+            // the king can't castle out of check, the target is checked once the move is made
+            if squares(span(king, king_target) & !square_bb(king_target) | square_bb(king)).any(|square| self.is_square_attacked(square, colour.opponent())) {continue}
+            moves.push(Move::new(king, king_target, CASTLE_FLAG));
+        }
+        moves
+    }
+
+    fn push_reference_pawn_moves(&self, moves: &mut MoveList, start: Square) {
+        let colour = self.colour_to_move();
+        let forward = if self.is_white_to_move {8} else {-8};
+        let push = |moves: &mut MoveList, target: Square| {
+            if has_square(RANK_1 | RANK_8, target) {
+                for promotion_type in [QUEEN, ROOK, BISHOP, KNIGHT] {
+                    moves.push(Move::new(start, target, Move::promotion_flag(promotion_type)));
+                }
+            } else {
+                moves.push(Move::new(start, target, NO_FLAG));
+            }
+        };
+        let one_step = start + forward;
+        if !has_square(self.occupied_bb(), one_step) {
+            push(moves, one_step);
+            let start_rank = if self.is_white_to_move {1} else {6};
+            if start / 8 == start_rank && !has_square(self.occupied_bb(), one_step + forward) {
+                moves.push(Move::new(start, one_step + forward, DOUBLE_PUSH_FLAG));
+            }
+        }
+        for &target in piece_attacks(PAWN, colour, start) {
+            if has_square(self.colour_bb(colour.opponent()), target) {
+                push(moves, target);
+            } else if target == self.en_passant {
+                moves.push(Move::new(start, target, EN_PASSANT_FLAG));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorMismatch {
+    pub fen: String,
+    pub missing: Vec<Move>,
+    pub extra: Vec<Move>,
+}
+
+impl std::fmt::Display for GeneratorMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let texts = |moves: &[Move]| moves.iter().map(|r#move| r#move.to_text()).collect::<Vec<_>>().join(" ");
+        write!(f, "{}: missing [{}] extra [{}]", self.fen, texts(&self.missing), texts(&self.extra))
+    }
+}
+
+// the moves generate_legal_moves misses or adds compared to the reference generator
+pub fn compare_generators(chess: &mut Chess) -> Option<GeneratorMismatch> {
+    let moves = chess.generate_legal_moves();
+    let reference = chess.generate_reference_moves();
+    let missing: Vec<Move> = reference.iter().copied().filter(|r#move| !moves.contains(r#move)).collect();
+    let extra: Vec<Move> = moves.iter().copied().filter(|r#move| !reference.contains(r#move)).collect();
+    if missing.is_empty() && extra.is_empty() {return None}
+    Some(GeneratorMismatch { fen: chess.to_fen(), missing, extra })
+}
+
+// plays random games from each position comparing the generators before every move,
+// a game stops at its first mismatch
+pub fn random_games_mismatches(fens: &[&str], games: usize, plies: usize, seed: u64) -> Vec<GeneratorMismatch> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut mismatches = Vec::new();
+    for fen in fens {
+        for _ in 0..games {
+            let mut chess = Chess::build(fen);
+            for _ in 0..plies {
+                if let Some(mismatch) = compare_generators(&mut chess) {
+                    mismatches.push(mismatch);
+                    break;
+                }
+                let moves = chess.generate_legal_moves();
+                if moves.is_empty() {break}
+                chess.make_move(moves[rng.gen_range(0..moves.len())]);
+            }
+        }
+    }
+    mismatches
+}
+
+// walks every position up to depth plies from the test positions 1 to 6, each first given to setup,
+// check runs on a position when it's reached and again once its moves have been taken back
+#[cfg(test)]
+pub fn walk_test_positions(depth: u16, setup: impl Fn(&mut Chess), mut check: impl FnMut(&mut Chess)) {
+    fn walk(chess: &mut Chess, depth: u16, check: &mut impl FnMut(&mut Chess)) {
+        check(chess);
+        if depth == 0 {return}
+        for r#move in chess.generate_legal_moves() {
+            chess.make_move(r#move);
+            walk(chess, depth - 1, check);
+            chess.unmake_move(r#move);
+        }
+        check(chess);
+    }
+    for num in 1..=6 {
+        let mut chess = Chess::position(num);
+        setup(&mut chess);
+        walk(&mut chess, depth, &mut check);
+    }
+}
+
+#[cfg(test)]
+mod reference_tests {
+    use super::*;
+
+    #[test]
+    fn random_games() {
+        let fens = [
+            START_POSITION,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "8/8/8/KPp4r/8/8/8/6k1 w - c6 0 1",
+            "4N1k1/1pb2nq1/1P2p1P1/3pP2p/2P5/7P/r2P3K/5r2 w - d6 0 33",
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            "1r4kr/8/8/8/8/8/8/1R4KR w BHbh - 0 1",
+        ];
+        let mismatches = random_games_mismatches(&fens, 20, 60, 0);
+        assert!(mismatches.is_empty(), "{}", mismatches.iter().map(|mismatch| mismatch.to_string()).collect::<Vec<_>>().join("\n"));
+    }
+
+    #[test]
+    fn attack_maps_behind_the_king() {
+        // the queen ray through the king used to go on past the rook
+        let mut chess = Chess::build("6R1/1k2r3/7P/8/3p1n2/Q7/2n5/1K6 w - - 11 104");
+        chess.make_move(Move::from_text(&chess, "a3a7"));
+        let fresh = Chess::build(&chess.to_fen());
+        for (side, fresh_side) in chess.side.iter().zip(&fresh.side) {
+            assert_eq!(side.attacks, fresh_side.attacks);
+            assert_eq!(side.piece_attacks, fresh_side.piece_attacks);
+        }
+    }
+
+    #[test]
+    fn reports_mismatches() {
+        for num in 1..=6 {
+            assert_eq!(compare_generators(&mut Chess::position(num)), None);
+        }
+        let mismatch = GeneratorMismatch {
+            fen: START_POSITION.to_string(),
+            missing: vec![Move::new(12, 28, DOUBLE_PUSH_FLAG)],
+            extra: vec![Move::new(12, 36, NO_FLAG), Move::new(4, 12, NO_FLAG)],
+        };
+        assert_eq!(mismatch.to_string(), format!("{START_POSITION}: missing [e2e4] extra [e2e5 e1e2]"));
+    }
+}
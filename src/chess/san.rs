@@ -0,0 +1,211 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    Malformed(String),
+    Illegal(String),
+    Ambiguous(String),
+}
+
+impl std::fmt::Display for SanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SanError::Malformed(san) => write!(f, "malformed move \"{san}\""),
+            SanError::Illegal(san) => write!(f, "illegal move \"{san}\""),
+            SanError::Ambiguous(san) => write!(f, "ambiguous move \"{san}\""),
+        }
+    }
+}
+impl std::error::Error for SanError {}
+
+impl Chess {
+    // Standard Algebraic Notation of a legal move in the current position
+    pub fn move_to_san(&mut self, r#move: Move) -> String {
+        let moves = self.generate_legal_moves();
+        debug_assert!(moves.contains(&r#move), "{} is not legal", r#move.to_text());
+        let (start, target) = (r#move.start(), r#move.target());
+        let piece_type = self.board(start).get_type();
+
+        let mut san = String::new();
+        if r#move.flag() == CASTLE_FLAG {
+            san += if r#move.is_king_castling() {"O-O"} else {"O-O-O"};
+        } else {
+            let is_capture = self.is_capture(r#move);
+            let start_text = square_to_text(start);
+            let (start_file, start_rank) = (start_text.chars().next().unwrap(), start_text.chars().nth(1).unwrap());
+            if piece_type == PAWN {
+                if is_capture {san.push(start_file)}
+            } else {
+                san.push(piece_type.symbol().to_ascii_uppercase());
+                let others = moves.iter()
+                    .filter(|other| other.target() == target && other.start() != start)
+                    .filter(|other| self.board(other.start()).is_type(piece_type))
+                    .map(|other| other.start());
+                let (mut is_ambiguous, mut same_file, mut same_rank) = (false, false, false);
+                for other in others {
+                    is_ambiguous = true;
+                    same_file |= other % 8 == start % 8;
+                    same_rank |= other / 8 == start / 8;
+                }
+                if is_ambiguous {
+                    if !same_file {san.push(start_file)}
+                    else if !same_rank {san.push(start_rank)}
+                    else {san += &start_text}
+                }
+            }
+            if is_capture {san.push('x')}
+            san += &square_to_text(target);
+            if r#move.is_promotion() {
+                san.push('=');
+                san.push(r#move.promotion_type().symbol().to_ascii_uppercase());
+            }
+        }
+
+        if self.gives_check(r#move) {
+            self.make_move(r#move);
+            san.push(if self.generate_legal_moves().is_empty() {'#'} else {'+'});
+            self.unmake_move(r#move);
+        }
+        san
+    }
+
+    // accepts check and annotation suffixes, 0-0 castling and promotions without '='
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let malformed = || SanError::Malformed(san.to_string());
+        let text = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        let moves = self.generate_legal_moves();
+
+        //castling
+        if ["O-O", "0-0", "O-O-O", "0-0-0"].contains(&text) {
+            let is_king_castling = text.len() == 3;
+            return moves.into_iter()
+                .find(|r#move| r#move.flag() == CASTLE_FLAG && r#move.is_king_castling() == is_king_castling)
+                .ok_or_else(|| SanError::Illegal(san.to_string()))
+        }
+        //piece type
+        let mut chars: Vec<char> = text.chars().collect();
+        let piece_type = match chars.first() {
+            Some(&symbol) if "NBRQK".contains(symbol) => {
+                chars.remove(0);
+                PieceType::from_symbol(symbol)
+            }
+            Some(_) => PAWN,
+            None => return Err(malformed()),
+        };
+        //promotion
+        let mut promotion = None;
+        if piece_type == PAWN && chars.last().is_some_and(|&symbol| "NBRQ".contains(symbol)) {
+            promotion = Some(PieceType::from_symbol(chars.pop().unwrap()));
+            if chars.last() == Some(&'=') {chars.pop();}
+        }
+        //target
+        if chars.len() < 2 {return Err(malformed())}
+        let (rank, file) = (chars.pop().unwrap(), chars.pop().unwrap());
+        if !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {return Err(malformed())}
+        let target = square_from_text(file, rank);
+        //start
+        if chars.last() == Some(&'x') {chars.pop();}
+        let (mut start_file, mut start_rank) = (None, None);
+        for ch in chars {
+            match ch {
+                'a'..='h' if start_file.is_none() && start_rank.is_none() => start_file = Some(ch as i8 - 'a' as i8),
+                '1'..='8' if start_rank.is_none() => start_rank = Some(ch as i8 - '1' as i8),
+                _ => return Err(malformed()),
+            }
+        }
+        // a pawn leaves its file only when capturing, and then the file is written
+        if piece_type == PAWN && start_file.is_none() {start_file = Some(target % 8)}
+
+        let mut found = moves.into_iter().filter(|r#move| {
+            let start = r#move.start();
+            r#move.target() == target
+            && self.board(start).is_type(piece_type)
+            && r#move.flag() != CASTLE_FLAG
+            && start_file.is_none_or(|file| start % 8 == file)
+            && start_rank.is_none_or(|rank| start / 8 == rank)
+            && promotion == r#move.is_promotion().then(|| r#move.promotion_type())
+        });
+        match (found.next(), found.next()) {
+            (Some(r#move), None) => Ok(r#move),
+            (None, _) => Err(SanError::Illegal(san.to_string())),
+            (Some(_), Some(_)) => Err(SanError::Ambiguous(san.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod san_tests {
+    use super::*;
+
+    fn san(fen: &str, text: &str) -> String {
+        let mut chess = Chess::build(fen);
+        let r#move = chess.generate_legal_moves().into_iter().find(|r#move| r#move.to_text() == text).unwrap();
+        chess.move_to_san(r#move)
+    }
+
+    #[test]
+    fn move_to_san() {
+        assert_eq!(san(START_POSITION, "e2e4"), "e4");
+        assert_eq!(san(START_POSITION, "g1f3"), "Nf3");
+        // castling and captures
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        assert_eq!(san(kiwipete, "e1g1"), "O-O");
+        assert_eq!(san(kiwipete, "e1c1"), "O-O-O");
+        assert_eq!(san(kiwipete, "d5e6"), "dxe6");
+        assert_eq!(san(kiwipete, "e2a6"), "Bxa6");
+        assert_eq!(san(kiwipete, "g2h3"), "gxh3");
+        // file, rank and square disambiguation
+        assert_eq!(san("4k3/8/8/8/8/8/4K3/R6R w - - 0 1", "a1d1"), "Rad1");
+        assert_eq!(san("4k3/8/8/8/R7/8/8/R3K3 w - - 0 1", "a1a2"), "R1a2");
+        assert_eq!(san("4k3/8/8/8/Q1Q5/8/Q7/4K3 w - - 0 1", "a4b3"), "Qa4b3");
+        // en passant and promotions
+        assert_eq!(san("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", "e5f6"), "exf6");
+        assert_eq!(san("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8n"), "a8=N");
+        assert_eq!(san("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q"), "axb8=Q+");
+        // check and mate
+        assert_eq!(san("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8#");
+        assert_eq!(san("6k1/5pp1/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8+");
+    }
+
+    #[test]
+    fn parse_san() {
+        let chess = Chess::start_position();
+        assert_eq!(chess.parse_san("e4").map(Move::to_text), Ok("e2e4".to_string()));
+        assert_eq!(chess.parse_san("Nf3!?").map(Move::to_text), Ok("g1f3".to_string()));
+        assert_eq!(chess.parse_san("e5"), Err(SanError::Illegal("e5".to_string())));
+        assert_eq!(chess.parse_san("Ke2"), Err(SanError::Illegal("Ke2".to_string())));
+        assert_eq!(chess.parse_san("O-O"), Err(SanError::Illegal("O-O".to_string())));
+        for malformed in ["", "Z4", "e9", "N", "N1af3", "e2-e4"] {
+            assert_eq!(chess.parse_san(malformed), Err(SanError::Malformed(malformed.to_string())));
+        }
+
+        let kiwipete = Chess::build("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert_eq!(kiwipete.parse_san("O-O").map(Move::to_text), Ok("e1g1".to_string()));
+        assert_eq!(kiwipete.parse_san("0-0-0").map(Move::to_text), Ok("e1c1".to_string()));
+        assert_eq!(kiwipete.parse_san("gxh3").map(Move::to_text), Ok("g2h3".to_string()));
+        assert_eq!(kiwipete.parse_san("Qxh3+").map(Move::to_text), Ok("f3h3".to_string()));
+
+        let rooks = Chess::build("4k3/8/8/8/8/8/4K3/R6R w - - 0 1");
+        assert_eq!(rooks.parse_san("Rd1"), Err(SanError::Ambiguous("Rd1".to_string())));
+        assert_eq!(rooks.parse_san("Rhd1").map(Move::to_text), Ok("h1d1".to_string()));
+
+        let promotion = Chess::build("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(promotion.parse_san("axb8=Q+").map(Move::to_text), Ok("a7b8q".to_string()));
+        assert_eq!(promotion.parse_san("a8N").map(Move::to_text), Ok("a7a8n".to_string()));
+        assert_eq!(promotion.parse_san("a8"), Err(SanError::Illegal("a8".to_string())));
+
+        let en_passant = Chess::build("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        assert_eq!(en_passant.parse_san("exf6").map(Move::flag), Ok(EN_PASSANT_FLAG));
+    }
+
+    #[test]
+    fn san_round_trip() {
+        for num in 1..=6 {
+            let mut chess = Chess::position(num);
+            for r#move in chess.generate_legal_moves() {
+                let san = chess.move_to_san(r#move);
+                assert_eq!(chess.parse_san(&san), Ok(r#move), "{san}");
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+impl Chess {
+    fn least_valuable_attacker(&self, attackers: Bitboard, colour: Colour) -> Option<(PieceType, Square)> {
+        [PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING].into_iter()
+            .map(|piece_type| (piece_type, attackers & self.pieces_bb(colour, piece_type)))
+            .find(|&(_, bitboard)| bitboard != EMPTY)
+            .map(|(piece_type, bitboard)| (piece_type, lsb(bitboard)))
+    }
+
+    // Static Exchange Evaluation: the material won by the side to move once every capture on the
+    // target square is played, each side taking with its least valuable piece and free to stop
+    pub fn see(&self, r#move: Move) -> Eval {
+        if r#move.flag() == CASTLE_FLAG {return 0}
+        let (start, target) = (r#move.start(), r#move.target());
+        let mut occupied = self.occupied_bb() ^ square_bb(start);
+        let mut gain = [0 as Eval; 32];
+        let mut attacker = self.board(start).get_type();
+
+        match r#move.flag() {
+            EN_PASSANT_FLAG => {
+                let eaten_square = if self.is_white_to_move {target - 8} else {target + 8};
+                occupied ^= square_bb(eaten_square);
+                gain[0] = PAWN.get_piece_value();
+            }
+            NO_FLAG | DOUBLE_PUSH_FLAG => {
+                let captured = self.board(target).get_type();
+                if captured != NONE_TYPE {gain[0] = captured.get_piece_value()}
+            }
+            _ => {
+                let captured = self.board(target).get_type();
+                if captured != NONE_TYPE {gain[0] = captured.get_piece_value()}
+                attacker = r#move.promotion_type();
+                gain[0] += attacker.get_piece_value() - PAWN.get_piece_value();
+            }
+        }
+
+        let diagonal_sliders = self.pieces_bb(WHITE, BISHOP) | self.pieces_bb(BLACK, BISHOP)
+            | self.pieces_bb(WHITE, QUEEN) | self.pieces_bb(BLACK, QUEEN);
+        let orthogonal_sliders = self.pieces_bb(WHITE, ROOK) | self.pieces_bb(BLACK, ROOK)
+            | self.pieces_bb(WHITE, QUEEN) | self.pieces_bb(BLACK, QUEEN);
+        let mut attackers = self.attackers_to_occupied(target, occupied) & occupied;
+        let mut colour = self.colour_to_move().opponent();
+        let mut depth = 0;
+        loop {
+            depth += 1;
+            // what the side to capture wins if the last capturing piece is taken
+            gain[depth] = attacker.get_piece_value() - gain[depth - 1];
+            let Some((piece_type, square)) = self.least_valuable_attacker(attackers, colour) else {break};
+            // the king can't take a defended piece
+            if piece_type == KING && attackers & self.colour_bb(colour.opponent()) != EMPTY {break}
+
+            occupied ^= square_bb(square);
+            // x-ray attackers behind the piece that just moved
+            if matches!(piece_type, PAWN | BISHOP | QUEEN) {
+                attackers |= bishop_attacks(target, occupied) & diagonal_sliders;
+            }
+            if matches!(piece_type, ROOK | QUEEN) {
+                attackers |= rook_attacks(target, occupied) & orthogonal_sliders;
+            }
+            attackers &= occupied;
+            attacker = piece_type;
+            colour = colour.opponent();
+        }
+        while depth > 1 {
+            depth -= 1;
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        }
+        gain[0]
+    }
+
+    pub fn see_ge(&self, r#move: Move, threshold: Eval) -> bool {
+        self.see(r#move) >= threshold
+    }
+}
+
+#[cfg(test)]
+mod see_tests {
+    use super::*;
+
+    fn see(fen: &str, text: &str) -> Eval {
+        let chess = Chess::build(fen);
+        let r#move = chess.generate_legal_moves().into_iter().find(|r#move| r#move.to_text() == text).unwrap();
+        chess.see(r#move)
+    }
+
+    #[test]
+    fn exchanges() {
+        assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), 100);
+        assert_eq!(see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"), -200);
+        assert_eq!(see("4k3/8/8/3q4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 900);
+        // moving onto an attacked square loses the piece
+        assert_eq!(see("4k3/8/8/8/8/2p5/8/3NK3 w - - 0 1", "d1b2"), -300);
+        assert_eq!(see(START_POSITION, "e2e4"), 0);
+        // en passant and promotions
+        assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+        assert_eq!(see("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), 800);
+        assert_eq!(see("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8q"), 1300);
+    }
+
+    #[test]
+    fn x_rays_and_king() {
+        // the second rook recaptures through the first one
+        assert_eq!(see("3r2k1/8/8/3p4/8/8/3R4/3R2K1 w - - 0 1", "d2d5"), 100);
+        assert_eq!(see("3r2k1/3r4/8/3p4/8/8/3R4/3R2K1 w - - 0 1", "d2d5"), -400);
+        // the king can't recapture while the rook defends the knight
+        assert_eq!(see("8/8/4k3/3p4/8/2N5/8/3RK3 w - - 0 1", "c3d5"), 100);
+        assert_eq!(see("8/8/4k3/3p4/8/2N5/8/4K3 w - - 0 1", "c3d5"), -200);
+
+        let chess = Chess::build("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1");
+        let r#move = Move::from_text(&chess, "d3e5");
+        assert!(chess.see_ge(r#move, -200));
+        assert!(!chess.see_ge(r#move, 0));
+    }
+}
@@ -0,0 +1,127 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    FiftyMoves,
+    SeventyFiveMoves,
+    InsufficientMaterial,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameResult {
+    pub outcome: ChessOutcome,
+    pub termination: Termination,
+}
+
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.outcome, self.termination) {
+            (ChessOutcome::WhiteWinner, _) => write!(f, "White mates"),
+            (ChessOutcome::BlackWinner, _) => write!(f, "Black mates"),
+            (ChessOutcome::Draw, termination) => write!(f, "Draw by {}", match termination {
+                Termination::Checkmate => unreachable!(),
+                Termination::Stalemate => "stalemate",
+                Termination::ThreefoldRepetition => "threefold repetition",
+                Termination::FivefoldRepetition => "fivefold repetition",
+                Termination::FiftyMoves => "fifty moves rule",
+                Termination::SeventyFiveMoves => "seventy-five moves rule",
+                Termination::InsufficientMaterial => "insufficient material",
+            }),
+        }
+    }
+}
+
+impl Chess {
+    // None while the game goes on; threefold repetition and the fifty moves rule are claimed right away
+    pub fn game_result(&self, moves: &[Move]) -> Option<GameResult> {
+        let draw = |termination| Some(GameResult { outcome: ChessOutcome::Draw, termination });
+        if moves.is_empty() {
+            if !self.in_check() {return draw(Termination::Stalemate)}
+            let outcome = if self.is_white_to_move {ChessOutcome::BlackWinner} else {ChessOutcome::WhiteWinner};
+            return Some(GameResult { outcome, termination: Termination::Checkmate });
+        }
+        if self.is_insufficient_material() {return draw(Termination::InsufficientMaterial)}
+        // the current position is not counted by get_repetitions
+        let repetitions = self.get_repetitions();
+        if repetitions >= 4 {return draw(Termination::FivefoldRepetition)}
+        if self.half_move >= 150 {return draw(Termination::SeventyFiveMoves)}
+        if repetitions >= 2 {return draw(Termination::ThreefoldRepetition)}
+        if self.half_move >= 100 {return draw(Termination::FiftyMoves)}
+        None
+    }
+
+    // no sequence of legal moves can lead to a mate: lone kings, a single minor piece, or only bishops on one colour
+    pub fn is_insufficient_material(&self) -> bool {
+        let pieces = |piece_type| self.pieces_bb(WHITE, piece_type) | self.pieces_bb(BLACK, piece_type);
+        if pieces(PAWN) | pieces(ROOK) | pieces(QUEEN) != EMPTY {return false}
+        let (knights, bishops) = (pieces(KNIGHT), pieces(BISHOP));
+        if (knights | bishops).count_ones() <= 1 {return true}
+        knights == EMPTY && (squares(bishops).all(is_black_square) || !squares(bishops).any(is_black_square))
+    }
+
+    // inside the search a single repetition is already a draw
+    pub fn is_search_draw(&self) -> bool {
+        self.is_repeated_or_dead() || self.half_move >= 100
+    }
+    // the draws that hold before knowing if there's a legal move, a mate still beats the fifty moves rule
+    pub fn is_repeated_or_dead(&self) -> bool {
+        self.get_repetitions() >= 1 || self.is_insufficient_material()
+    }
+}
+
+#[cfg(test)]
+mod termination_tests {
+    use super::*;
+
+    fn result(fen: &str) -> Option<GameResult> {
+        let chess = Chess::build(fen);
+        chess.game_result(&chess.generate_legal_moves())
+    }
+
+    #[test]
+    fn game_results() {
+        let draw = |termination| Some(GameResult { outcome: ChessOutcome::Draw, termination });
+        assert_eq!(result(START_POSITION), None);
+        assert_eq!(result("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1"), None);
+        assert_eq!(result("R5k1/5ppp/8/8/8/8/8/4K3 b - - 0 1"), Some(GameResult { outcome: ChessOutcome::WhiteWinner, termination: Termination::Checkmate }));
+        assert_eq!(result("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"), draw(Termination::Stalemate));
+        assert_eq!(result("4k3/8/8/8/8/8/4P3/4K3 w - - 100 80"), draw(Termination::FiftyMoves));
+        assert_eq!(result("4k3/8/8/8/8/8/4P3/4K3 w - - 150 80"), draw(Termination::SeventyFiveMoves));
+        // mate on the hundredth half move still counts
+        assert_eq!(result("R5k1/5ppp/8/8/8/8/8/4K3 b - - 100 80").unwrap().termination, Termination::Checkmate);
+        assert_eq!(result("4k3/8/8/8/8/3B4/8/4KB2 w - - 0 1"), draw(Termination::InsufficientMaterial));
+
+        let mut chess = Chess::start_position();
+        let knights = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        let mut terminations = Vec::new();
+        for _ in 0..4 {
+            for text in knights {
+                chess.make_move(Move::from_text(&chess, text));
+            }
+            terminations.push(chess.game_result(&chess.generate_legal_moves()).map(|result| result.termination));
+        }
+        assert_eq!(terminations, [None, Some(Termination::ThreefoldRepetition), Some(Termination::ThreefoldRepetition), Some(Termination::FivefoldRepetition)]);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        for (fen, expected) in [
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/4KN2 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/4KB2 w - - 0 1", true),
+            // bishops on the same colour, f1 and c8
+            ("2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1", true),
+            ("1b2k3/8/8/8/8/8/8/4KB2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/3NKN2 w - - 0 1", false),
+            ("4kn2/8/8/8/8/8/8/4KB2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/4KR2 w - - 0 1", false),
+            ("4k3/7p/8/8/8/8/8/4K3 w - - 0 1", false),
+        ] {
+            assert_eq!(Chess::build(fen).is_insufficient_material(), expected, "{fen}");
+        }
+    }
+}
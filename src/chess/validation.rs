@@ -0,0 +1,138 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionError {
+    KingCount(Colour, usize),
+    PawnOnBackRank(Square),
+    OpponentInCheck,
+    ImpossibleCheck,
+    CastlingRights(u8),
+    EnPassant(Square),
+}
+
+impl std::fmt::Display for PositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let colour_name = |colour: Colour| if colour == WHITE {"white"} else {"black"};
+        match *self {
+            PositionError::KingCount(colour, count) => write!(f, "{} has {count} kings", colour_name(colour)),
+            PositionError::PawnOnBackRank(square) => write!(f, "pawn on back rank at {}", utils::square_to_text(square)),
+            PositionError::OpponentInCheck => write!(f, "the side not to move is in check"),
+            PositionError::ImpossibleCheck => write!(f, "the side to move is in an impossible check"),
+            PositionError::CastlingRights(right) => write!(f, "castling right {} without king and rook at home", castling_symbol(right)),
+            PositionError::EnPassant(square) => write!(f, "inconsistent en passant square {}", utils::square_to_text(square)),
+        }
+    }
+}
+
+fn castling_symbol(right: u8) -> char {
+    match right {
+        CASTLE_WHITE_KING => 'K',
+        CASTLE_WHITE_QUEEN => 'Q',
+        CASTLE_BLACK_KING => 'k',
+        CASTLE_BLACK_QUEEN => 'q',
+        _ => unreachable!(),
+    }
+}
+
+impl Chess {
+    pub fn validate(&self) -> Vec<PositionError> {
+        let mut errors = Vec::new();
+        // Kings
+        let mut kings_ok = true;
+        for colour in [WHITE, BLACK] {
+            let count = self.board.iter().filter(|&&piece| piece == Piece::new(KING, colour)).count();
+            if count != 1 {
+                errors.push(PositionError::KingCount(colour, count));
+                kings_ok = false;
+            }
+        }
+        // Pawns
+        for square in squares((self.pieces_bb(WHITE, PAWN) | self.pieces_bb(BLACK, PAWN)) & (RANK_1 | RANK_8)) {
+            errors.push(PositionError::PawnOnBackRank(square));
+        }
+        // Checks
+        if kings_ok {
+            let (colour, opponent) = (self.colour_to_move(), self.colour_to_move().opponent());
+            if self.is_square_attacked(self.side[opponent.colour_index()].king, colour) {
+                errors.push(PositionError::OpponentInCheck);
+            }
+            // a double check always has a slider behind the piece that moved
+            let checkers = self.checkers();
+            let sliders = [BISHOP, ROOK, QUEEN].into_iter()
+                .map(|piece_type| (checkers & self.pieces_bb(opponent, piece_type)).count_ones())
+                .sum::<u32>();
+            let total = checkers.count_ones();
+            if total > 2 || (total == 2 && sliders == 0) {
+                errors.push(PositionError::ImpossibleCheck);
+            }
+        }
+        // Castling
+        for colour in [WHITE, BLACK] {
+            let colour_index = colour.colour_index();
+            let back_rank = if colour == WHITE {0} else {7};
+            let king = self.side[colour_index].king;
+            for side in [KING_SIDE, QUEEN_SIDE] {
+                let right = CASTLE_RIGHTS[colour_index][side];
+                if self.castling & right == 0 {continue}
+                let rook = self.castling_rooks[colour_index][side];
+                let is_home = self.board(king) == Piece::new(KING, colour) && king / 8 == back_rank
+                    && self.board(rook) == Piece::new(ROOK, colour) && rook / 8 == back_rank
+                    && (rook > king) == (side == KING_SIDE);
+                if !is_home {
+                    errors.push(PositionError::CastlingRights(right));
+                }
+            }
+        }
+        // En passant
+        if self.en_passant != -1 {
+            let colour = self.colour_to_move();
+            let forward = if colour == WHITE {8} else {-8};
+            let (rank, square) = (self.en_passant / 8, self.en_passant);
+            let is_consistent = rank == if colour == WHITE {5} else {2}
+                && self.board(square) == NONE
+                && self.board(square + forward) == NONE
+                && self.board(square - forward) == Piece::new(PAWN, colour.opponent());
+            if !is_consistent {
+                errors.push(PositionError::EnPassant(square));
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn legal_positions() {
+        for num in 1..=6 {
+            assert_eq!(Chess::position(num).validate(), vec![]);
+        }
+        assert_eq!(Chess::build("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").validate(), vec![]);
+    }
+
+    #[test]
+    fn illegal_positions() {
+        let errors = [
+            ("4k3/8/8/8/8/8/8/4K2K w - - 0 1", vec![PositionError::KingCount(WHITE, 2)]),
+            ("8/8/8/8/8/8/8/4K3 w - - 0 1", vec![PositionError::KingCount(BLACK, 0)]),
+            ("4k3/8/8/8/8/8/8/P3K3 w - - 0 1", vec![PositionError::PawnOnBackRank(0)]),
+            ("4k3/8/8/8/8/8/8/4K2R w - - 0 1", vec![]),
+            ("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1", vec![PositionError::OpponentInCheck]),
+            ("4k3/8/8/8/8/3n1n2/8/4K3 w - - 0 1", vec![PositionError::ImpossibleCheck]),
+            ("4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1", vec![]),
+            ("4k3/8/8/8/8/8/8/4K3 w K - 0 1", vec![PositionError::CastlingRights(CASTLE_WHITE_KING)]),
+            ("4k3/8/8/8/8/8/8/R3K3 w Qk - 0 1", vec![PositionError::CastlingRights(CASTLE_BLACK_KING)]),
+            ("4k3/8/8/8/8/8/8/1R1K4 w B - 0 1", vec![]),
+            ("4k3/8/8/8/8/8/8/1R1K4 w A - 0 1", vec![PositionError::CastlingRights(CASTLE_WHITE_QUEEN)]),
+            ("4k3/8/8/8/8/8/3K4/1R6 w B - 0 1", vec![PositionError::CastlingRights(CASTLE_WHITE_QUEEN)]),
+            ("4k3/8/8/4p3/8/8/8/4K3 w - e6 0 1", vec![]),
+            ("4k3/8/8/8/8/8/8/4K3 w - e6 0 1", vec![PositionError::EnPassant(44)]),
+            ("4k3/8/8/4p3/8/8/8/4K3 b - e6 0 1", vec![PositionError::EnPassant(44)]),
+        ];
+        for (fen, expected) in errors {
+            assert_eq!(Chess::build(fen).validate(), expected, "{fen}");
+        }
+    }
+}
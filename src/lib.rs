@@ -1,88 +1,147 @@
 pub mod chess;
 pub mod player;
+pub mod pgn;
 
-use std::sync::Mutex;
+use std::sync::{Mutex, LazyLock, atomic::AtomicBool};
 use std::{thread::sleep, time::Duration};
 
 pub use chess::*;
 pub use player::*;
+pub use pgn::*;
 
 pub use std::io::Write;
 pub use std::fs::File;
 pub use std::path::PathBuf;
 // SETTINGS
-pub static mut DISPLAY: bool = true;
-static ROOT_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+pub static DISPLAY: AtomicBool = AtomicBool::new(true);
+// ~/.chess-AI, created the first time a log or a game is written; None without a home
+static ROOT_PATH: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    let path = PathBuf::from(std::env::var_os("HOME")?).join(".chess-AI");
+    std::fs::create_dir_all(&path).ok()?;
+    Some(path)
+});
+pub fn root_path() -> Option<&'static PathBuf> { ROOT_PATH.as_ref() }
 
+// nothing is logged until clear_log opens the file
 pub static  LOG: Mutex<Option<File>> = Mutex::new(None);
 pub fn write_to_log(message: &str) {
-    (*LOG.lock().unwrap()).as_mut().unwrap().write(message.as_bytes()).unwrap();
-    (*LOG.lock().unwrap()).as_mut().unwrap().write("\n".as_bytes()).unwrap();
+    if let Some(log) = LOG.lock().unwrap().as_mut() {
+        log.write_all(message.as_bytes()).unwrap();
+        log.write_all("\n".as_bytes()).unwrap();
+    }
 }
 pub fn clear_log() {
-    let path = ROOT_PATH.lock().unwrap().as_ref().unwrap().join("chess_log.txt");
-    *LOG.lock().unwrap() = Some(File::create(path).unwrap());
+    *LOG.lock().unwrap() = root_path().map(|path| File::create(path.join("chess_log.txt")).unwrap());
 }
 
 pub fn benchmark(depth: u16) {
     let mut chess = Chess::start_position();
     chess.time_perft(depth);
+    chess.set_attack_maps(false);
+    chess.time_perft(depth);
 }
 
 pub fn compete(player_1: &mut dyn ChessPlayer, player_2: &mut dyn ChessPlayer, games: u32) {
+    let mut pgn_file = root_path().map(|path| {
+        std::fs::OpenOptions::new().create(true).append(true).open(path.join("games.pgn")).unwrap()
+    });
     let (mut won, mut lost, mut draw) = (0, 0, 0);
     for n in 0..games {
         println!("Game {} of {games}", n+1);
         let mut chess = Chess::start_position();
+        let (mut game, result);
         if n % 2 == 0 {
-            let outcome = play(&mut chess, player_1, player_2, None);
-            match outcome {
+            (game, result) = play_recorded(&mut chess, player_1, player_2, None);
+            match result.outcome {
                 ChessOutcome::Draw => draw += 1,
                 ChessOutcome::WhiteWinner => won += 1,
                 ChessOutcome::BlackWinner => lost += 1,
             }
         }
         else {
-            let outcome = play(&mut chess, player_2, player_1, None);
-            match outcome {
+            (game, result) = play_recorded(&mut chess, player_2, player_1, None);
+            match result.outcome {
                 ChessOutcome::Draw => draw += 1,
                 ChessOutcome::WhiteWinner => lost += 1,
                 ChessOutcome::BlackWinner => won += 1,
             }
         }
+        game.set_tag("Event", "compete");
+        game.set_tag("Round", &(n+1).to_string());
+        if let Some(pgn_file) = pgn_file.as_mut() {
+            writeln!(pgn_file, "{}", game.to_pgn()).unwrap();
+        }
+        println!("{result}. Final position: {}", chess.to_fen());
         println!("[P1] Won: {won} Draw: {draw} Lost: {lost}");
     }
 }
 
-pub fn play(chess: &mut Chess, player_1: &mut dyn ChessPlayer, player_2: &mut dyn ChessPlayer, time: Option<Duration>) -> ChessOutcome {
+pub fn play(chess: &mut Chess, player_1: &mut dyn ChessPlayer, player_2: &mut dyn ChessPlayer, time: Option<Duration>) -> GameResult {
+    play_recorded(chess, player_1, player_2, time).1
+}
+
+// same as play, keeping the game as a PGN record
+pub fn play_recorded(chess: &mut Chess, player_1: &mut dyn ChessPlayer, player_2: &mut dyn ChessPlayer, time: Option<Duration>) -> (PgnGame, GameResult) {
+    let mut game = PgnGame::default();
+    game.set_tag("Date", &pgn_date());
+    let (white, black) = (player_1.name().to_string(), player_2.name().to_string());
+    let (white, black) = if chess.colour_to_move() == WHITE {(white, black)} else {(black, white)};
+    game.set_tag("White", &white);
+    game.set_tag("Black", &black);
+    if chess.to_fen() != START_POSITION {
+        game.set_tag("SetUp", "1");
+        game.set_tag("FEN", &chess.to_fen());
+    }
+
     player_1.notify_new_game();
     player_2.notify_new_game();
     player_1.set_position(&chess);
     player_2.set_position(&chess);
     chess.display();
-    let mut moves;
-    loop {
-        moves = chess.generate_legal_moves();
-        if chess.is_finished(&moves) {break}
+    let result = loop {
+        let moves = chess.generate_legal_moves();
+        if let Some(result) = chess.game_result(&moves) {break result}
         sleep(Duration::from_millis(1));
-        let (r#move, _) = player_1.best_move(chess, time);
+        let (r#move, eval) = player_1.best_move(chess, time);
+        record_move(&mut game, chess, r#move, eval, player_1.search_depth());
         chess.make_move(r#move);
         player_1.make_move(r#move);
         player_2.make_move(r#move);
         chess.update_display(r#move);
 
-        moves = chess.generate_legal_moves();
-        if chess.is_finished(&moves) {break}
+        let moves = chess.generate_legal_moves();
+        if let Some(result) = chess.game_result(&moves) {break result}
         sleep(Duration::from_millis(1));
-        let (r#move, _) = player_2.best_move(chess, time);
+        let (r#move, eval) = player_2.best_move(chess, time);
+        record_move(&mut game, chess, r#move, eval, player_2.search_depth());
         chess.make_move(r#move);
         player_1.make_move(r#move);
         player_2.make_move(r#move);
         chess.update_display(r#move);
+    };
+
+    match game.moves.last_mut() {
+        Some(last) => last.comments.push(result.to_string()),
+        None => game.comments.push(result.to_string()),
     }
-    chess.get_outcome(&moves)
+    game.result = Some(result.outcome);
+    (game, result)
 }
 
+fn record_move(game: &mut PgnGame, chess: &mut Chess, r#move: Move, eval: Eval, depth: Option<u16>) {
+    let san = chess.move_to_san(r#move);
+    write_to_log(&san);
+    // {eval/depth} from the point of view of the side that moved
+    let comments = depth.map(|depth| {
+        let eval = match eval {
+            Eval::MAX => "+M".to_string(),
+            eval if eval == -Eval::MAX => "-M".to_string(),
+            eval => format!("{:+.2}", eval as f32 / 100.0),
+        };
+        format!("{eval}/{depth}")
+    }).into_iter().collect();
+    game.moves.push(PgnMove { r#move, san, nags: Vec::new(), comments, variations: Vec::new() });
+}
 
 #[cfg(test)]
 mod tests {
@@ -104,4 +163,24 @@ mod tests {
         assert_eq!(chess.board(10), Piece::new(PAWN, WHITE));
         assert_eq!(chess.board(18), NONE);
     }
+
+    #[test]
+    fn play_recorded_game() {
+        let mut chess = Chess::build("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+        let mut boss = BossPlayer::new();
+        boss.depth = 2;
+        let (game, result) = play_recorded(&mut chess, &mut BadPlayer::new(), &mut boss, None);
+        assert_eq!(game.result, Some(result.outcome));
+        assert_eq!(game.moves.last().unwrap().comments.last(), Some(&result.to_string()));
+        assert_eq!(game.tag("White"), Some("Random Player"));
+        assert_eq!(game.tag("Black"), Some("RizziTheBoss"));
+        assert_eq!(game.tag("FEN"), Some("4k3/8/8/8/8/8/8/4K2R w K - 0 1"));
+        // only the searching player comments its moves
+        assert!(game.moves.iter().step_by(2).all(|r#move| r#move.comments.len() <= 1));
+        assert!(game.moves.iter().skip(1).step_by(2).all(|r#move| r#move.comments[0].contains('/')));
+
+        let reread = PgnGame::parse(&game.to_pgn()).unwrap();
+        assert_eq!(reread.moves, game.moves);
+        assert_eq!(reread.result, game.result);
+    }
 }
@@ -1,5 +1,5 @@
 
-use std::{process::exit, sync::{atomic::Ordering, mpsc::{self, Receiver, SyncSender}}, time::Duration};
+use std::{process::exit, sync::{atomic::Ordering, mpsc::{self, Receiver, SyncSender}}, time::{Duration, Instant}};
 use chess_rust::*;
 
 mod analysis;
@@ -21,13 +21,87 @@ fn analyse_db(db_file_name: &str, max_search: i32, time_per_move: Option<f32>) {
     if !did { println!("file {to_file_path:?} already exists")}
 }
 
+fn perft(fen: &str, depth: u16, threads: usize) {
+    let mut chess = Chess::from_fen(fen).unwrap_or_else(|error| panic!("invalid fen: {error}")).without_attack_maps();
+    let t_start = Instant::now();
+    let nodes = if threads > 1 {
+        chess.perft_parallel(depth, threads)
+    } else {
+        let divide = chess.perft_divide(depth);
+        for (r#move, nodes) in &divide {
+            println!("{}: {nodes}", r#move.to_text());
+        }
+        divide.iter().map(|(_, nodes)| nodes).sum()
+    };
+    println!("\nNodes: {nodes} Time: {:?}", t_start.elapsed());
+}
+
+// the perft depths start at 1, anything else prints the usage and exits
+fn depth_arg(arg: Option<String>, usage: &str) -> u16 {
+    match arg.and_then(|arg| arg.parse::<u16>().ok()) {
+        Some(depth) if depth >= 1 => depth,
+        _ => {
+            println!("expected: {usage} with a depth of at least 1");
+            exit(2)
+        }
+    }
+}
+
+// #return: true if every count matches
+fn perft_suite(file_name: &str, max_depth: u16, threads: usize) -> bool {
+    let text = std::fs::read_to_string(file_name).unwrap_or_else(|error| panic!("can't read {file_name}: {error}"));
+    let cases = parse_perft_suite(&text).unwrap_or_else(|error| panic!("{file_name}: {error}"));
+    let t_start = Instant::now();
+    let mismatches = run_perft_suite(&cases, max_depth, threads);
+    for mismatch in &mismatches {
+        println!("{mismatch}");
+    }
+    println!("{} positions, {} mismatches, time: {:?}", cases.len(), mismatches.len(), t_start.elapsed());
+    mismatches.is_empty()
+}
+
 fn main() {
     // -help
     if std::env::args().find(|arg| ["-h", "--help"].contains(&arg.as_str())).is_some() {
         println!("These are to export the move evaluations of games in a DB:\n\t-analyse <max_games_to_export> #this start exporting\n\t-db <filename.pgn> #the games to export\n\t-boss <sec_per_move> #to make and take boss player evaluations");
+        println!("These are to test the move generator:\n\t-perft <depth> #nodes under each move and in total\n\t-perft-suite <file.epd> <max_depth> #compare the counts of an EPD suite\n\t-fen <fen> #the position to perft, the start position by default\n\t-threads <num> #threads to share the root moves\n\t-benchmark <depth> #time perft with and without the attack maps");
         exit(0);
     }
 
+    // -threads
+    let mut args = std::env::args();
+    let threads = match args.find(|arg| arg == "-threads") {
+        Some(_) => args.next().expect("expected: -threads <num>").parse::<usize>().unwrap(),
+        None => 1,
+    };
+
+    // -perft
+    let mut args = std::env::args();
+    if args.find(|arg| arg == "-perft").is_some() {
+        let depth = depth_arg(args.next(), "-perft <depth>");
+        // -fen
+        args = std::env::args();
+        let fen = if args.find(|arg| arg == "-fen").is_some() { args.next().expect("expected: -fen <fen>") } else { START_POSITION.to_string() };
+        perft(&fen, depth, threads);
+        exit(0);
+    }
+
+    // -benchmark
+    let mut args = std::env::args();
+    if args.find(|arg| arg == "-benchmark").is_some() {
+        let depth = depth_arg(args.next(), "-benchmark <depth>");
+        benchmark(depth);
+        exit(0);
+    }
+
+    // -perft-suite
+    let mut args = std::env::args();
+    if args.find(|arg| arg == "-perft-suite").is_some() {
+        let file_name = args.next().expect("expected: -perft-suite <file.epd> <max_depth>");
+        let max_depth = args.next().expect("expected: -perft-suite <file.epd> <max_depth>").parse::<u16>().unwrap();
+        exit(if perft_suite(&file_name, max_depth, threads) {0} else {1});
+    }
+
     // -analyse
     let mut args = std::env::args();
     if args.find(|arg| arg == "-analyse").is_some() {
@@ -46,7 +120,7 @@ fn main() {
     }
 
     //uci engine
-    legal_moves::precompute();
+    clear_log();
     
     let mut engine = BossPlayer::new().into_engine_uci();
     let th_stop = engine.stop.clone();
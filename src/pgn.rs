@@ -0,0 +1,554 @@
+use std::io::BufRead;
+
+use crate::chess::*;
+
+#[derive(Debug)]
+pub enum PgnError {
+    Io(std::io::Error),
+    Tag(String),
+    Fen(FenError),
+    San(SanError),
+    Syntax(String),
+}
+
+impl std::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnError::Io(error) => write!(f, "{error}"),
+            PgnError::Tag(tag) => write!(f, "bad tag pair \"{tag}\""),
+            PgnError::Fen(error) => write!(f, "bad FEN tag: {error}"),
+            PgnError::San(error) => write!(f, "{error}"),
+            PgnError::Syntax(message) => write!(f, "{message}"),
+        }
+    }
+}
+impl std::error::Error for PgnError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnMove {
+    pub r#move: Move,
+    pub san: String,
+    pub nags: Vec<u8>,
+    pub comments: Vec<String>,
+    // alternatives to this move, each starting from the position before it
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    // comments before the first move
+    pub comments: Vec<String>,
+    pub moves: Vec<PgnMove>,
+    // None for an unfinished game (*)
+    pub result: Option<ChessOutcome>,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+    pub fn start_position(&self) -> Result<Chess, FenError> {
+        Chess::from_fen(self.tag("FEN").unwrap_or(START_POSITION))
+    }
+
+    pub fn parse(text: &str) -> Result<PgnGame, PgnError> {
+        let mut tokens = tokenize(text)?.into_iter().peekable();
+        let mut tags = Vec::new();
+        while let Some(Token::Tag(name, value)) = tokens.peek() {
+            tags.push((name.clone(), value.clone()));
+            tokens.next();
+        }
+        let mut game = PgnGame { tags, ..Default::default() };
+        let mut chess = game.start_position().map_err(PgnError::Fen)?;
+        (game.moves, game.comments) = parse_line(&mut tokens, &mut chess, 0)?;
+        match tokens.next() {
+            Some(Token::Result(result)) => game.result = result,
+            None => (),
+            Some(_) => return Err(PgnError::Syntax("unexpected token after the result".to_string())),
+        }
+        if tokens.next().is_some() {return Err(PgnError::Syntax("unexpected token after the result".to_string()))}
+        Ok(game)
+    }
+}
+
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const MAX_LINE_LENGTH: usize = 80;
+
+impl PgnGame {
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old_value)) => *old_value = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    // the moves played in the chess game from the position it was set up in, up to a null move
+    pub fn from_history(chess: &Chess) -> PgnGame {
+        let mut start = chess.clone();
+        while start.undo_last().is_some() {}
+        let mut game = PgnGame::default();
+        if start.to_fen() != START_POSITION {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &start.to_fen());
+        }
+        for &r#move in chess.moves_played().iter().take_while(|&&r#move| r#move != NONE_MOVE) {
+            let san = start.move_to_san(r#move);
+            start.make_move(r#move);
+            game.moves.push(PgnMove { r#move, san, nags: Vec::new(), comments: Vec::new(), variations: Vec::new() });
+        }
+        game
+    }
+
+    // export format: the seven tag roster first, movetext wrapped at 80 columns
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => result_text(&self.result).to_string(),
+                "Date" => self.tag(name).unwrap_or("????.??.??").to_string(),
+                _ => self.tag(name).unwrap_or("?").to_string(),
+            };
+            pgn += &format!("[{name} \"{}\"]\n", escape(&value));
+        }
+        for (name, value) in self.tags.iter().filter(|(name, _)| !SEVEN_TAG_ROSTER.contains(&name.as_str())) {
+            pgn += &format!("[{name} \"{}\"]\n", escape(value));
+        }
+        pgn.push('\n');
+
+        let mut tokens: Vec<String> = self.comments.iter().map(|comment| format!("{{{comment}}}")).collect();
+        let chess = self.start_position().unwrap_or_else(|_| Chess::start_position());
+        write_line(&mut tokens, &self.moves, chess.full_turn, chess.colour_to_move() == WHITE, !self.comments.is_empty());
+        tokens.push(result_text(&self.result).to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            pgn += &token;
+        }
+        pgn.push('\n');
+        pgn
+    }
+}
+
+fn result_text(result: &Option<ChessOutcome>) -> &'static str {
+    match result {
+        Some(ChessOutcome::WhiteWinner) => "1-0",
+        Some(ChessOutcome::BlackWinner) => "0-1",
+        Some(ChessOutcome::Draw) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn write_line(tokens: &mut Vec<String>, moves: &[PgnMove], mut full_move: u16, mut is_white: bool, mut needs_number: bool) {
+    needs_number |= !is_white;
+    for r#move in moves {
+        if is_white {
+            tokens.push(format!("{full_move}."));
+        } else if needs_number {
+            tokens.push(format!("{full_move}..."));
+        }
+        tokens.push(r#move.san.clone());
+        tokens.extend(r#move.nags.iter().map(|nag| format!("${nag}")));
+        tokens.extend(r#move.comments.iter().map(|comment| format!("{{{comment}}}")));
+        for variation in &r#move.variations {
+            let mut variation_tokens = Vec::new();
+            write_line(&mut variation_tokens, variation, full_move, is_white, true);
+            if let Some(first) = variation_tokens.first_mut() {first.insert(0, '(')}
+            if let Some(last) = variation_tokens.last_mut() {last.push(')')}
+            tokens.extend(variation_tokens);
+        }
+        needs_number = !r#move.comments.is_empty() || !r#move.variations.is_empty();
+        if !is_white {full_move += 1}
+        is_white = !is_white;
+    }
+}
+
+// today's date as yyyy.mm.dd, in UTC
+pub fn pgn_date() -> String {
+    let seconds = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    // days to civil date, see https://howardhinnant.github.io/date_algorithms.html
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {month_index + 3} else {month_index - 9};
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    Open,
+    Close,
+    Result(Option<ChessOutcome>),
+    San(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            _ if ch.is_whitespace() => {chars.next();}
+            '[' => {
+                chars.next();
+                let mut tag = String::new();
+                let mut in_quotes = false;
+                loop {
+                    match chars.next() {
+                        None => return Err(PgnError::Tag(tag)),
+                        Some('\\') if in_quotes => tag.push(chars.next().unwrap_or('\\')),
+                        Some('"') => {in_quotes = !in_quotes; tag.push('"')}
+                        Some(']') if !in_quotes => break,
+                        Some(ch) => tag.push(ch),
+                    }
+                }
+                tokens.push(parse_tag(&tag)?);
+            }
+            '{' => {
+                chars.next();
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Err(PgnError::Syntax("comment without its closing }".to_string())),
+                        Some('}') => break,
+                        Some(ch) => comment.push(ch),
+                    }
+                }
+                tokens.push(Token::Comment(comment.trim().to_string()));
+            }
+            ';' => {
+                let comment: String = chars.by_ref().skip(1).take_while(|&ch| ch != '\n').collect();
+                tokens.push(Token::Comment(comment.trim().to_string()));
+            }
+            '(' => {chars.next(); tokens.push(Token::Open)}
+            ')' => {chars.next(); tokens.push(Token::Close)}
+            '$' => {
+                chars.next();
+                let mut nag = String::new();
+                while let Some(&digit) = chars.peek().filter(|ch| ch.is_ascii_digit()) {
+                    nag.push(digit);
+                    chars.next();
+                }
+                let nag = nag.parse().map_err(|_| PgnError::Syntax(format!("bad NAG \"${nag}\"")))?;
+                tokens.push(Token::Nag(nag));
+            }
+            _ => {
+                let mut symbol = String::new();
+                while let Some(&ch) = chars.peek().filter(|ch| !ch.is_whitespace() && !"[]{}();$".contains(**ch)) {
+                    symbol.push(ch);
+                    chars.next();
+                }
+                push_symbol(&mut tokens, &symbol)?;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_tag(tag: &str) -> Result<Token, PgnError> {
+    let bad_tag = || PgnError::Tag(tag.to_string());
+    let (name, value) = tag.trim().split_once(char::is_whitespace).ok_or_else(bad_tag)?;
+    let value = value.trim().strip_prefix('"').and_then(|value| value.strip_suffix('"')).ok_or_else(bad_tag)?;
+    if name.is_empty() || !name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_') {return Err(bad_tag())}
+    Ok(Token::Tag(name.to_string(), value.to_string()))
+}
+
+fn push_symbol(tokens: &mut Vec<Token>, symbol: &str) -> Result<(), PgnError> {
+    let result = match symbol {
+        "1-0" => Some(Some(ChessOutcome::WhiteWinner)),
+        "0-1" => Some(Some(ChessOutcome::BlackWinner)),
+        "1/2-1/2" => Some(Some(ChessOutcome::Draw)),
+        "*" => Some(None),
+        _ => None,
+    };
+    if let Some(result) = result {
+        tokens.push(Token::Result(result));
+        return Ok(())
+    }
+    // move numbers: "12." "12..." or glued to the move as in "12.Nf3"
+    let mut san = symbol;
+    if !symbol.starts_with("0-0") && symbol.starts_with(|ch: char| ch.is_ascii_digit()) {
+        san = symbol.trim_start_matches(|ch: char| ch.is_ascii_digit()).trim_start_matches('.');
+        if san.is_empty() {return Ok(())}
+        if san.len() == symbol.trim_start_matches(|ch: char| ch.is_ascii_digit()).len() {
+            return Err(PgnError::Syntax(format!("unexpected symbol \"{symbol}\"")))
+        }
+    }
+    // suffix annotations are the short form of the first six NAGs
+    let move_text = san.trim_end_matches(['!', '?']);
+    let nag = match &san[move_text.len()..] {
+        "" => None,
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        annotation => return Err(PgnError::Syntax(format!("bad annotation \"{annotation}\""))),
+    };
+    tokens.push(Token::San(move_text.to_string()));
+    if let Some(nag) = nag {tokens.push(Token::Nag(nag))}
+    Ok(())
+}
+
+// parses moves until the end of the line, then takes them back so the position is as it was
+fn parse_line(tokens: &mut std::iter::Peekable<std::vec::IntoIter<Token>>, chess: &mut Chess, depth: u32) -> Result<(Vec<PgnMove>, Vec<String>), PgnError> {
+    let mut moves: Vec<PgnMove> = Vec::new();
+    let mut comments = Vec::new();
+    loop {
+        match tokens.peek() {
+            None if depth > 0 => return Err(PgnError::Syntax("unterminated variation".to_string())),
+            None | Some(Token::Result(_)) if depth == 0 => break,
+            Some(Token::Result(_)) => return Err(PgnError::Syntax("result inside a variation".to_string())),
+            Some(Token::Close) if depth == 0 => return Err(PgnError::Syntax("unexpected ')'".to_string())),
+            Some(Token::Close) => {tokens.next(); break}
+            _ => (),
+        }
+        match tokens.next().unwrap() {
+            Token::San(san) => {
+                let r#move = chess.parse_san(&san).map_err(PgnError::San)?;
+                chess.make_move(r#move);
+                moves.push(PgnMove { r#move, san, nags: Vec::new(), comments: Vec::new(), variations: Vec::new() });
+            }
+            Token::Comment(comment) => match moves.last_mut() {
+                Some(last) => last.comments.push(comment),
+                None => comments.push(comment),
+            }
+            Token::Nag(nag) => match moves.last_mut() {
+                Some(last) => last.nags.push(nag),
+                None => return Err(PgnError::Syntax(format!("NAG ${nag} before the first move"))),
+            }
+            Token::Open => {
+                let Some(last) = moves.last_mut() else {
+                    return Err(PgnError::Syntax("variation before the first move".to_string()))
+                };
+                chess.unmake_move(last.r#move);
+                let (mut variation, variation_comments) = parse_line(tokens, chess, depth + 1)?;
+                chess.make_move(last.r#move);
+                if let Some(first) = variation.first_mut() {
+                    first.comments.splice(0..0, variation_comments);
+                }
+                last.variations.push(variation);
+            }
+            Token::Tag(name, _) => return Err(PgnError::Syntax(format!("tag pair {name} inside the movetext"))),
+            Token::Close | Token::Result(_) => unreachable!(),
+        }
+    }
+    for r#move in moves.iter().rev() {
+        chess.unmake_move(r#move.r#move);
+    }
+    Ok((moves, comments))
+}
+
+// streams games one at a time, a malformed game comes back as an error and is skipped
+pub struct PgnReader<R: BufRead> {
+    reader: R,
+    pending: Option<String>,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> PgnReader<R> {
+        PgnReader { reader, pending: None }
+    }
+
+    // the text of the next game: its tag pairs up to the next tag pair after some movetext
+    fn next_game_text(&mut self) -> std::io::Result<Option<String>> {
+        let mut text = String::new();
+        let (mut in_comment, mut has_movetext, mut after_blank) = (false, false, false);
+        loop {
+            let line = match self.pending.take() {
+                Some(line) => line,
+                None => {
+                    let mut line = String::new();
+                    if self.reader.read_line(&mut line)? == 0 {
+                        return Ok((!text.trim().is_empty()).then_some(text))
+                    }
+                    line
+                }
+            };
+            let trimmed = line.trim_start();
+            // a tag pair after a blank line starts the next game even if a comment was never closed
+            if in_comment && after_blank && trimmed.starts_with('[') {
+                self.pending = Some(line);
+                return Ok(Some(text))
+            }
+            after_blank = trimmed.is_empty();
+            if !in_comment {
+                // escape mechanism
+                if line.starts_with('%') {continue}
+                if trimmed.starts_with('[') {
+                    if has_movetext {
+                        self.pending = Some(line);
+                        return Ok(Some(text))
+                    }
+                    text += &line;
+                    continue
+                }
+            }
+            for ch in line.chars() {
+                match ch {
+                    '}' if in_comment => in_comment = false,
+                    _ if in_comment => (),
+                    '{' => in_comment = true,
+                    ';' => break,
+                    _ if !ch.is_whitespace() => has_movetext = true,
+                    _ => (),
+                }
+            }
+            has_movetext |= in_comment;
+            text += &line;
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_game_text() {
+            Ok(text) => text.map(|text| PgnGame::parse(&text)),
+            Err(error) => Some(Err(PgnError::Io(error))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod pgn_tests {
+    use super::*;
+
+    const GAMES: &str = r#"[Event "Casual \"blitz\""]
+[Site "?"]
+[White "Rizzi"]
+[Black "Boss"]
+[Result "1-0"]
+
+{Opening comment} 1. e4 e5 2. Nf3 {the knight} $1 (2. Bc4 Nc6 (2... Nf6 3. d3) 3. Qh5) 2... Nc6
+3. Bb5!? a6 ; Morphy defence
+4. Ba4 Nf6 5. O-O Be7 1-0
+
+[Event "Broken"]
+
+1. e4 e5 2. Ke3 *
+
+[Event "From FEN"]
+[SetUp "1"]
+[FEN "4k3/P7/8/8/8/8/8/4K3 w - - 0 1"]
+
+1.a8=Q+ Kd7 2.Qb7+ 1/2-1/2
+"#;
+
+    #[test]
+    fn reads_games() {
+        let games: Vec<_> = PgnReader::new(GAMES.as_bytes()).collect();
+        assert_eq!(games.len(), 3);
+
+        let game = games[0].as_ref().unwrap();
+        assert_eq!(game.tag("Event"), Some("Casual \"blitz\""));
+        assert_eq!(game.tag("White"), Some("Rizzi"));
+        assert_eq!(game.comments, vec!["Opening comment".to_string()]);
+        assert_eq!(game.result, Some(ChessOutcome::WhiteWinner));
+        let sans: Vec<&str> = game.moves.iter().map(|r#move| r#move.san.as_str()).collect();
+        assert_eq!(sans, vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O", "Be7"]);
+        assert_eq!(game.moves[8].r#move, Move::new(4, 6, CASTLE_FLAG));
+
+        let knight = &game.moves[2];
+        assert_eq!(knight.comments, vec!["the knight".to_string()]);
+        assert_eq!(knight.nags, vec![1]);
+        assert_eq!(knight.variations.len(), 1);
+        let variation = &knight.variations[0];
+        assert_eq!(variation.iter().map(|r#move| r#move.san.as_str()).collect::<Vec<_>>(), vec!["Bc4", "Nc6", "Qh5"]);
+        assert_eq!(variation[1].variations[0].iter().map(|r#move| r#move.san.as_str()).collect::<Vec<_>>(), vec!["Nf6", "d3"]);
+        assert_eq!(game.moves[4].nags, vec![5]);
+        assert_eq!(game.moves[5].comments, vec!["Morphy defence".to_string()]);
+
+        assert!(matches!(games[1], Err(PgnError::San(SanError::Illegal(_)))));
+
+        let game = games[2].as_ref().unwrap();
+        assert_eq!(game.result, Some(ChessOutcome::Draw));
+        assert_eq!(game.moves.len(), 3);
+        assert_eq!(game.moves[0].r#move, Move::new(48, 56, PROMOTE_TO_QUEEN_FLAG));
+    }
+
+    #[test]
+    fn malformed_games() {
+        for text in [
+            "[Event \"Unclosed\"\n\n1. e4 *",
+            "[Event \"?\"]\n\n1. e4 (e5 *",
+            "[Event \"?\"]\n\n1. e4 e5) *",
+            "[Event \"?\"]\n\n( 1. e4 ) *",
+            "[Event \"?\"]\n\n1. e4 e5 *  2. Nf3",
+            "[Event \"?\"]\n\n1. e4!!! *",
+            "[FEN \"8/8/8 w - - 0 1\"]\n\n*",
+            "[Event \"?\"]\n\n1. e4 {unterminated e5 *",
+        ] {
+            assert!(PgnGame::parse(text).is_err(), "{text}");
+        }
+        // the comment left open doesn't take the next games with it
+        let text = "[Event \"A\"]\n\n1. e4 {unterminated e5 *\n\n[Event \"B\"]\n\n1. d4 *\n\n[Event \"C\"]\n\n1. c4 *\n";
+        let games: Vec<_> = PgnReader::new(text.as_bytes()).collect();
+        assert_eq!(games.len(), 3);
+        assert!(matches!(games[0], Err(PgnError::Syntax(_))));
+        assert_eq!(games[1].as_ref().unwrap().tag("Event"), Some("B"));
+        assert_eq!(games[2].as_ref().unwrap().tag("Event"), Some("C"));
+        assert_eq!(PgnGame::parse("1. e4 e5").unwrap().result, None);
+    }
+
+    #[test]
+    fn lichess_comments() {
+        let text = "[Event \"Rated Blitz game\"]\n[WhiteElo \"1639\"]\n\n1. e4 { [%eval 0.17] } 1... c5 { [%eval 0.19] } 2. Nf3?! { [%eval -0.05] } 0-1\n";
+        let game = PgnReader::new(text.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(game.moves.len(), 3);
+        assert_eq!(game.moves[1].comments, vec!["[%eval 0.19]".to_string()]);
+        assert_eq!(game.moves[2].nags, vec![6]);
+        assert_eq!(game.result, Some(ChessOutcome::BlackWinner));
+    }
+
+    #[test]
+    fn writes_games() {
+        let game = PgnReader::new(GAMES.as_bytes()).next().unwrap().unwrap();
+        let pgn = game.to_pgn();
+        assert!(pgn.starts_with("[Event \"Casual \\\"blitz\\\"\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"Rizzi\"]\n[Black \"Boss\"]\n[Result \"1-0\"]\n\n"));
+        assert!(pgn.replace('\n', " ").contains("{Opening comment} 1. e4 e5 2. Nf3 $1 {the knight} (2. Bc4 Nc6 (2... Nf6 3. d3) 3. Qh5) 2... Nc6"));
+        assert!(pgn.ends_with("O-O Be7 1-0\n"));
+        assert!(pgn.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+
+        let reread = PgnGame::parse(&pgn).unwrap();
+        assert_eq!(reread.moves, game.moves);
+        assert_eq!(reread.to_pgn(), pgn);
+
+        let game = PgnReader::new(GAMES.as_bytes()).nth(2).unwrap().unwrap();
+        assert!(game.to_pgn().contains("[SetUp \"1\"]\n[FEN \"4k3/P7/8/8/8/8/8/4K3 w - - 0 1\"]\n\n1. a8=Q+ Kd7 2. Qb7+ 1/2-1/2\n"));
+        let black_first = PgnGame::parse("[FEN \"4k3/8/8/8/8/8/8/4K3 b - - 0 7\"]\n\nKd7 Kd2 *").unwrap();
+        assert!(black_first.to_pgn().ends_with("\n\n7... Kd7 8. Kd2 *\n"));
+
+        let mut chess = Chess::build("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+        for text in ["a7a8q", "e8d7", "a8b7"] {
+            chess.make_move(Move::from_text(&chess, text));
+        }
+        let history = PgnGame::from_history(&chess);
+        assert_eq!(history.moves, game.moves.iter().map(|r#move| PgnMove { comments: Vec::new(), ..r#move.clone() }).collect::<Vec<_>>());
+        assert_eq!(history.tag("FEN"), game.tag("FEN"));
+
+        let date = pgn_date();
+        assert_eq!(date.len(), 10);
+        assert!(date.split('.').all(|part| part.parse::<u32>().is_ok()));
+    }
+}
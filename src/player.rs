@@ -28,25 +28,28 @@ pub trait ChessPlayer {
     fn make_move(&mut self, r#move: Move);
     fn evaluate_infinite(&mut self, chess: &mut Chess, send_info: fn(depth: u16, eval: Eval, time: u64, nodes: u32, nps: u32, pv: Move));
     fn get_stop(&self) -> Arc<AtomicBool> {Arc::new(AtomicBool::new(false))}
+    // depth reached by the last best_move, None if the player doesn't search
+    fn search_depth(&self) -> Option<u16> {None}
 }
 
 pub struct EngineUCI<PLAYER: ChessPlayer> {
     chess: Chess,
     player: PLAYER,
     pub stop: Arc<AtomicBool>,
-    log: File,
+    chess960: bool,
+    log: Option<File>,
 }
 
 impl<PLAYER: ChessPlayer> EngineUCI<PLAYER> {
     pub fn new(player: PLAYER) -> Self {
         let stop = player.get_stop();
-        let path = ROOT_PATH.lock().unwrap().as_ref().unwrap().join("uci_log.txt");
+        let log = root_path().map(|path| File::create(path.join("uci_log.txt")).unwrap());
         EngineUCI {
             chess: Chess::new(),
             player: player,
             stop,
-            log:
-            File::create(path).unwrap()
+            chess960: false,
+            log,
         }
     }
 
@@ -55,21 +58,22 @@ impl<PLAYER: ChessPlayer> EngineUCI<PLAYER> {
     }
 
     pub fn received_command(&mut self, message: &str) {
-        self.log.write_all("Scid: ".as_bytes()).unwrap();
-        self.log.write_all(message.as_bytes()).unwrap();
-        self.log.write_all("\n".as_bytes()).unwrap();
+        self.write_log("Scid: ", message);
 
         let (msg, arg) = message.split_once(' ').unwrap_or((message, ""));
         match msg {
             "uci" => {
                 self.respond(&format!("id name {}", self.player.name()));
                 self.respond("id author Stefano R");
+                self.respond("option name UCI_Chess960 type check default false");
                 self.respond("uciok")
             },
             "isready" => self.respond("readyok"),
+            "setoption" => self.process_setoption_command(arg),
             "ucinewgame" => self.process_new_game_command(),
             "position" => self.process_position_command(arg),
             "go" => self.process_go_command(arg),
+            "d" => self.respond(&format!("Fen: {}", self.chess.to_fen())),
             "stop" => (),
             "quit" => (),
             _ => println!("Huh? {msg}"),
@@ -78,16 +82,32 @@ impl<PLAYER: ChessPlayer> EngineUCI<PLAYER> {
 
     pub fn respond(&mut self, message: &str) {
         println!("{message}");
-        self.log.write_all("Engine: ".as_bytes()).unwrap();
-        self.log.write_all(message.as_bytes()).unwrap();
-        self.log.write_all("\n".as_bytes()).unwrap();
+        self.write_log("Engine: ", message);
+    }
+
+    fn write_log(&mut self, from: &str, message: &str) {
+        let Some(log) = self.log.as_mut() else {return};
+        log.write_all(from.as_bytes()).unwrap();
+        log.write_all(message.as_bytes()).unwrap();
+        log.write_all("\n".as_bytes()).unwrap();
     }
 
     fn process_new_game_command(&mut self) {
-        self.chess.irreversable_state.clear();
+        self.chess.clear_history();
         self.player.notify_new_game();
     }
 
+    fn process_setoption_command(&mut self, message: &str) {
+        let Some((name, value)) = message.strip_prefix("name ").and_then(|option| option.split_once(" value ")) else {
+            self.respond(&format!("info string invalid setoption command: {message}"));
+            return
+        };
+        match name.trim() {
+            "UCI_Chess960" => self.chess960 = value.trim() == "true",
+            _ => self.respond(&format!("info string unknown option: {name}")),
+        }
+    }
+
     fn process_position_command(&mut self, message: &str) {
         let (msg, arg) = message.split_once(' ').unwrap_or((message, ""));
         let (fen, moves) = arg.split_once("moves").unwrap_or((arg, ""));
@@ -98,20 +118,39 @@ impl<PLAYER: ChessPlayer> EngineUCI<PLAYER> {
                 self.player.set_position(&self.chess);
             },
             "fen" => {
-                let mut new_chess = Chess::build(fen);
-                std::mem::swap(&mut new_chess.irreversable_state, &mut self.chess.irreversable_state);
-                new_chess.irreversable_state.push((NONE_TYPE, self.chess.en_passant, self.chess.castling, self.chess.half_move, self.chess.hash()));
+                let mut new_chess = match Chess::from_fen_validated(fen) {
+                    Ok(chess) => chess,
+                    Err(error) => {
+                        self.respond(&format!("info string invalid fen: {error}"));
+                        return
+                    }
+                };
+                if self.chess960 {new_chess.set_key_set(KeySet::Chess960)}
+                // the earlier positions are kept for the repetitions, not as moves to take back
+                std::mem::swap(&mut new_chess.history, &mut self.chess.history);
+                new_chess.history.push(StateInfo {
+                    captured: NONE_TYPE,
+                    en_passant: self.chess.en_passant,
+                    castling: self.chess.castling,
+                    half_move: self.chess.half_move,
+                    full_turn: self.chess.full_turn,
+                    hash: self.chess.hash(),
+                    checkers: self.chess.checkers(),
+                });
                 self.chess = new_chess;
                 self.player.set_position(&self.chess);
             }
-            _ => unreachable!()
+            _ => {
+                self.respond(&format!("info string invalid position command: {message}"));
+                return
+            }
         }
 
-        for r#move in moves {
-            let r#move = Move::from_text(
-                &self.chess,
-                r#move,
-            );
+        for text in moves {
+            let Some(r#move) = Move::parse_legal(&self.chess, text) else {
+                self.respond(&format!("info string illegal move: {text}"));
+                return
+            };
             self.chess.make_move(r#move);
             self.player.make_move(r#move);
         }
@@ -121,6 +160,16 @@ impl<PLAYER: ChessPlayer> EngineUCI<PLAYER> {
         let mut msgs = message.split_whitespace();
         let mut move_time = 1_000;
 
+        let moves = self.chess.generate_legal_moves();
+        if let Some(result) = self.chess.game_result(&moves) {
+            self.respond(&format!("info string {result}"));
+            // claimable draws are left to the GUI
+            if moves.is_empty() {
+                self.respond("bestmove (none)");
+                return
+            }
+        }
+
         match msgs.next() {
             Some("infinite") => {
                 fn send_depth_score (depth: u16, eval: Eval, time: u64, nodes: u32, nps: u32, pv: Move) {
@@ -150,7 +199,8 @@ impl<PLAYER: ChessPlayer> EngineUCI<PLAYER> {
         
         let (best_move, _) = self.player.best_move(&mut self.chess, Some(Duration::from_millis(move_time)));
 
-        self.respond(&format!("bestmove {}", best_move.to_text()));
+        let best_move_text = if self.chess960 {best_move.to_text_960(&self.chess)} else {best_move.to_text()};
+        self.respond(&format!("bestmove {best_move_text}"));
                     
         self.chess.make_move(best_move);
         self.player.make_move(best_move);
@@ -22,16 +22,18 @@ impl ChessPlayer for HumanPlayer {
             let mut text = String::new();
             /*
             write_to_log("\nHashes:");
-            for s in &chess.irreversable_state {
-                write_to_log(&format!("{:?}", s.4));
+            for state in &chess.history {
+                write_to_log(&format!("{:?}", state.hash));
             }*/
             
             write!(io::stderr(), "{}: ", self.name).unwrap(); io::stderr().flush().unwrap();
             io::stdin().read_line(&mut text).unwrap();
             write!(io::stderr(), "\r\x1b[A\x1b[J").unwrap(); io::stderr().flush().unwrap();
             
-            let r#move = Move::from_text(&chess, &text.trim());
-            if chess.generate_legal_moves().contains(&r#move) {
+            // SAN or long algebraic notation
+            let text = text.trim();
+            let r#move = chess.parse_san(text).ok().or_else(|| Move::parse_legal(chess, text));
+            if let Some(r#move) = r#move {
                 return (r#move, 0);
             }
         }
@@ -7,19 +7,22 @@ use super::*;
 pub mod transposition_table;
 pub mod evaluation;
 pub mod search;
+pub mod move_picker;
 pub use transposition_table::*;
+pub use move_picker::*;
 
 pub struct BossPlayer {
     pub transposition_table: TranspositionTable,
     pub evaluated: u32,
     pub depth: u16,
+    pub search_depth: u16,
     pub search_canceled: Arc<AtomicBool>,
     pub nodes: u32,
     pub print_info: bool,
 }
 
 impl BossPlayer {
-    pub fn new() -> BossPlayer {BossPlayer { transposition_table: TranspositionTable::new(), evaluated: 0, depth: 4, search_canceled: Arc::new(AtomicBool::new(false)), nodes: 0, print_info: true }}
+    pub fn new() -> BossPlayer {BossPlayer { transposition_table: TranspositionTable::new(), evaluated: 0, depth: 4, search_depth: 0, search_canceled: Arc::new(AtomicBool::new(false)), nodes: 0, print_info: true }}
 }
 
 impl ChessPlayer for BossPlayer {
@@ -27,8 +30,11 @@ impl ChessPlayer for BossPlayer {
     fn notify_new_game(&self) {}
     fn set_position(&mut self, chess: &Chess) {}
     fn get_stop(&self) -> Arc<AtomicBool> {self.search_canceled.clone()}
+    fn search_depth(&self) -> Option<u16> {Some(self.search_depth)}
 
     fn best_move(&mut self, chess: &mut Chess, time: Option<Duration>) -> (Move, Eval) {
+        // the evaluation and the move ordering read the attack maps
+        if !chess.has_attack_maps() {chess.set_attack_maps(true)}
         let max_depth = if time.is_none() {self.depth} else {u16::MAX};
         let t_start = Instant::now();
         
@@ -81,8 +87,8 @@ impl ChessPlayer for BossPlayer {
         ));
         
         /*write_to_log("\nHashes {");
-        for s in &chess.irreversable_state {
-            write_to_log(&format!("  {:?}", s.4));
+        for state in &chess.history {
+            write_to_log(&format!("  {:?}", state.hash));
         }
         write_to_log(&format!("}}\nRepetitions: {}", chess.get_repetitions()));
         */
@@ -93,6 +99,7 @@ impl ChessPlayer for BossPlayer {
         chess: &mut Chess,
         send_info: fn(depth: u16, eval: Eval, time: u64, nodes: u32, nps: u32, pv: Move),
     ) {
+        if !chess.has_attack_maps() {chess.set_attack_maps(true)}
         self.search_canceled.store(false, Ordering::Relaxed);
 
         clear_log();
@@ -217,10 +224,34 @@ mod boss_tests {
         }
     }
 
+    #[test]
+    fn builds_the_attack_maps() {
+        let search = |chess: &mut Chess| {
+            let mut boss = BossPlayer::new();
+            (boss.depth, boss.print_info) = (3, false);
+            boss.best_move(chess, None)
+        };
+        let mut chess = Chess::position(2);
+        let mut without_maps = chess.without_attack_maps();
+        assert_eq!(search(&mut without_maps), search(&mut chess));
+        assert!(without_maps.has_attack_maps());
+    }
+    #[test]
+    fn mate_beats_fifty_moves() {
+        let mut boss = BossPlayer::new();
+        let mut mated = Chess::build("R5k1/5ppp/8/8/8/8/8/4K3 b - - 100 80");
+        assert_eq!(boss.search_ab(&mut mated, 2, -Eval::MAX, Eval::MAX), -Eval::MAX);
+        assert_eq!(boss.search_all_captures(&mut mated, -Eval::MAX, Eval::MAX), -Eval::MAX);
+        // Ra8 mates on the hundredth half move
+        let mut chess = Chess::build("6k1/5ppp/8/8/8/8/8/R3K3 w - - 99 80");
+        assert_eq!(boss.search_ab(&mut chess, 2, -Eval::MAX, Eval::MAX), Eval::MAX);
+        let mut chess = Chess::build("4k3/8/8/8/8/8/4P3/4K3 w - - 100 80");
+        assert_eq!(boss.search_ab(&mut chess, 2, -Eval::MAX, Eval::MAX), 0);
+    }
     fn boss_fight(fen: &str, outcome_reference: ChessOutcome, time: Option<Duration>) {
         let mut chess = Chess::build(fen);
-        let outcome = play(&mut chess, &mut BossPlayer::new(), &mut BossPlayer::new(), time);
-        assert_eq!(outcome, outcome_reference);
+        let result = play(&mut chess, &mut BossPlayer::new(), &mut BossPlayer::new(), time);
+        assert_eq!(result.outcome, outcome_reference);
     }
     #[test]
     fn checkmate_in_one() {
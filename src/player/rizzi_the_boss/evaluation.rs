@@ -14,6 +14,7 @@ const QUEEN_VALUE: Eval = 900;
 
 impl BossPlayer {
     pub fn evaluate(&mut self, chess: &mut Chess) -> Eval {
+        debug_assert!(chess.has_attack_maps());
         self.evaluated += 1;
 
         let white_material = self.count_material(chess, WHITE.colour_index());
@@ -134,7 +135,7 @@ impl BossPlayer {
         let mut defense_control = 0;
         let king_squares = piece_attacks(KING, colour, king);
         let num_squares = king_squares.len();
-        for &mut square in king_squares {
+        for &square in king_squares {
             enemy_control += chess.side[colour.opponent().colour_index()].attacks[square as usize];
             if chess.board(square) != NONE { defense_piece += 1 }
             defense_control += chess.side[colour.colour_index()].attacks[square as usize] - 1;
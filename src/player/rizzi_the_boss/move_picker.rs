@@ -0,0 +1,129 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stage { TTMove, GoodCaptures, Quiets, BadCaptures, Done }
+
+// yields the TT move, then the captures, then the quiet moves and last the captures
+// that lose material, generating each group only when the search gets to it
+pub struct MovePicker {
+    stage: Stage,
+    tt_move: Move,
+    captures: Option<MoveList>,
+    quiets: Option<MoveList>,
+    capture_index: usize,
+    // the captures losing material by SEE are moved to the front of the list as they are found
+    bad_captures: usize,
+    bad_index: usize,
+    quiet_index: usize,
+}
+
+impl MovePicker {
+    pub fn new(tt_move: Move) -> MovePicker {
+        MovePicker { stage: Stage::TTMove, tt_move, captures: None, quiets: None, capture_index: 0, bad_captures: 0, bad_index: 0, quiet_index: 0 }
+    }
+
+    fn generated<'a>(list: &'a mut Option<MoveList>, chess: &Chess, gen_type: GenType) -> &'a mut MoveList {
+        list.get_or_insert_with(|| {
+            let mut moves = chess.generate_moves(gen_type);
+            moves.score_moves(|r#move| BossPlayer::guess_move_score(chess, r#move) as i32);
+            moves.sort_by_score();
+            moves
+        })
+    }
+
+    pub fn next_move(&mut self, chess: &Chess) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::TTMove => {
+                    self.stage = Stage::GoodCaptures;
+                    if self.tt_move == NONE_MOVE {continue}
+                    // a hash collision can leave a move from another position
+                    if chess.is_legal(self.tt_move) {return Some(self.tt_move)}
+                    self.tt_move = NONE_MOVE;
+                }
+                Stage::GoodCaptures => {
+                    let moves = MovePicker::generated(&mut self.captures, chess, GenType::Captures);
+                    let Some(&r#move) = moves.get(self.capture_index) else {
+                        self.stage = Stage::Quiets;
+                        continue
+                    };
+                    if r#move == self.tt_move {
+                        // left out of both groups
+                    } else if chess.see_ge(r#move, 0) {
+                        self.capture_index += 1;
+                        return Some(r#move)
+                    } else {
+                        moves.swap(self.bad_captures, self.capture_index);
+                        self.bad_captures += 1;
+                    }
+                    self.capture_index += 1;
+                }
+                Stage::Quiets => {
+                    let moves = MovePicker::generated(&mut self.quiets, chess, GenType::Quiets);
+                    if let Some(&r#move) = moves.get(self.quiet_index) {
+                        self.quiet_index += 1;
+                        if r#move != self.tt_move {return Some(r#move)}
+                    } else {
+                        self.stage = Stage::BadCaptures;
+                    }
+                }
+                Stage::BadCaptures => {
+                    let moves = MovePicker::generated(&mut self.captures, chess, GenType::Captures);
+                    if self.bad_index < self.bad_captures {
+                        self.bad_index += 1;
+                        return Some(moves[self.bad_index - 1])
+                    }
+                    self.stage = Stage::Done;
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod move_picker_tests {
+    use super::*;
+
+    fn picked(chess: &Chess, mut picker: MovePicker) -> Vec<Move> {
+        let mut moves = Vec::new();
+        while let Some(r#move) = picker.next_move(chess) {
+            moves.push(r#move);
+        }
+        moves
+    }
+
+    #[test]
+    fn picks_every_move_once() {
+        for num in 1..=6 {
+            let chess = Chess::position(num);
+            let mut all = chess.generate_legal_moves();
+            let tt_move = all[all.len() / 2];
+            let mut moves = picked(&chess, MovePicker::new(tt_move));
+            assert_eq!(moves[0], tt_move);
+            all.sort_by_key(|r#move| r#move.to_text());
+            moves.sort_by_key(|r#move| r#move.to_text());
+            assert_eq!(all[..], moves[..]);
+        }
+    }
+
+    #[test]
+    fn captures_before_quiets() {
+        let chess = Chess::position(2);
+        let moves = picked(&chess, MovePicker::new(NONE_MOVE));
+        let mut captures = chess.generate_moves(GenType::Captures);
+        captures.score_moves(|r#move| BossPlayer::guess_move_score(&chess, r#move) as i32);
+        captures.sort_by_score();
+        let (good_captures, bad_captures): (Vec<Move>, Vec<Move>) = captures.iter().partition(|&&r#move| chess.see_ge(r#move, 0));
+        assert!(!good_captures.is_empty() && !bad_captures.is_empty());
+        assert_eq!(moves[..good_captures.len()], good_captures[..]);
+        assert_eq!(moves[moves.len() - bad_captures.len()..], bad_captures[..]);
+    }
+
+    #[test]
+    fn illegal_tt_move_is_skipped() {
+        let chess = Chess::start_position();
+        let moves = picked(&chess, MovePicker::new(Move::new(4, 36, NO_FLAG)));
+        assert_eq!(moves.len(), 20);
+    }
+}
@@ -9,16 +9,14 @@ impl BossPlayer {
         send_info: impl Fn(u16, Eval, u64, u32, u32, Move),
     ) -> (Move, Eval) {
         self.evaluated = 0;
+        self.search_depth = 0;
         let hash = chess.hash();
         let mut moves = chess.generate_legal_moves();
-        if chess.is_finished_for_real(&moves) {
-            let white_winner_eval =
-            if chess.colour_to_move() == WHITE { Eval::MAX }
-            else { -Eval::MAX };
-            match chess.get_outcome(&moves) {
-                ChessOutcome::Draw => return (NONE_MOVE, 0),
-                ChessOutcome::WhiteWinner => return (NONE_MOVE, white_winner_eval),
-                ChessOutcome::BlackWinner => return (NONE_MOVE, -white_winner_eval),
+        // a draw that can only be claimed still needs a move
+        if moves.is_empty() {
+            match chess.game_result(&moves).map(|result| result.termination) {
+                Some(Termination::Checkmate) => return (NONE_MOVE, -Eval::MAX),
+                _ => return (NONE_MOVE, 0),
             }
         }
         let mut best_move = moves.first().unwrap().clone();
@@ -54,6 +52,7 @@ impl BossPlayer {
             let mut time = t_start.elapsed();
             if time.is_zero() { time = Duration::from_secs(1) }
             let nps = self.nodes as f32 / time.as_secs_f32();
+            self.search_depth = depth_iter;
             send_info(depth_iter, best_eval, time.as_millis() as u64, self.nodes, nps as u32, best_move);
             
             if best_eval == Eval::MAX || best_eval == -Eval::MAX {break}
@@ -67,12 +66,8 @@ impl BossPlayer {
         if depth == 0 {return self.evaluate(chess)}
 
         let moves = chess.generate_legal_moves();
-        if chess.is_finished(&moves) {
-            match chess.get_outcome(&moves) {
-                ChessOutcome::Draw => return 0,
-                _ => return -Eval::MAX,
-            }
-        }
+        if moves.is_empty() {return if chess.in_check() {-Eval::MAX} else {0}}
+        if chess.is_search_draw() {return 0}
 
         let mut best_evaluation = -Eval::MAX;
         for r#move in moves {
@@ -89,12 +84,10 @@ impl BossPlayer {
         if depth == 0 {return self.evaluate(chess)}
 
         let mut moves = chess.generate_legal_moves();
-        if chess.is_finished(&moves) {
+        if moves.is_empty() || chess.is_search_draw() {
             self.evaluated += 1;
-            match chess.get_outcome(&moves) {
-                ChessOutcome::Draw => return 0,
-                _ => return -Eval::MAX,
-            }
+            if moves.is_empty() && chess.in_check() {return -Eval::MAX}
+            return 0
         }
         
         if order {
@@ -120,15 +113,12 @@ impl BossPlayer {
         self.nodes += 1;
         if depth == 0 {return self.search_all_captures(chess, alpha, beta)}
 
-        let mut moves = chess.generate_legal_moves();
-        if chess.is_finished(&moves) {
+        if chess.is_repeated_or_dead() {
             self.evaluated += 1;
-            match chess.get_outcome(&moves) {
-                ChessOutcome::Draw => return 0,
-                _ => return -Eval::MAX,
-            }
+            return 0;
         }
         
+        let mut tt_move = NONE_MOVE;
         if let Some(entry) = self.transposition_table.get_entry(chess.hash()/*, &chess.board*/) {
             if entry.depth >= depth {
                 match entry.node {
@@ -137,12 +127,21 @@ impl BossPlayer {
                     TypeNode::Cut => if entry.score >= beta {return beta}
                 }
             }
+            tt_move = entry.r#move;
         }
-        self.order_moves(chess, &mut moves);
+        let mut picker = MovePicker::new(tt_move);
 
         let mut node = TypeNode::All;
-        let mut best_move = moves.first().unwrap().clone();
-        for r#move in moves {
+        let mut best_move = NONE_MOVE;
+        while let Some(r#move) = picker.next_move(chess) {
+            if best_move == NONE_MOVE {
+                // not mated, so the fifty moves rule holds
+                if chess.half_move >= 100 {
+                    self.evaluated += 1;
+                    return 0;
+                }
+                best_move = r#move;
+            }
             
             chess.make_move(r#move);
             let eval = -self.search_ab(chess, depth - 1, -beta, -alpha);
@@ -160,25 +159,51 @@ impl BossPlayer {
                 node = TypeNode::PV;
             }
         }
+        if best_move == NONE_MOVE {
+            self.evaluated += 1;
+            return if chess.in_check() {-Eval::MAX} else {0};
+        }
         self.transposition_table.put_entry(TableEntry::new(chess.hash(), node, best_move, depth, alpha/*, chess.board*/));
         alpha
     }
 
     pub fn search_all_captures(&mut self, chess: &mut Chess, mut alpha: Eval, beta: Eval) -> Eval {
-        let mut capture_moves = chess.generate_legal_moves();
-        if chess.is_finished(&capture_moves) {
+        if chess.is_repeated_or_dead() {
             self.evaluated += 1;
-            match chess.get_outcome(&capture_moves) {
-                ChessOutcome::Draw => return 0,
-                _ => return -Eval::MAX,
-            }
+            return 0;
         }
-        capture_moves.retain(|&r#move| chess.board(r#move.target()) != NONE || r#move.flag() == EN_PASSANT_FLAG);
+        // in check every evasion is searched, standing pat isn't allowed
+        let in_check = chess.in_check();
+        let mut capture_moves = if in_check {
+            let evasions = chess.generate_moves(GenType::Evasions);
+            if evasions.is_empty() {
+                self.evaluated += 1;
+                return -Eval::MAX;
+            }
+            if chess.half_move >= 100 {
+                self.evaluated += 1;
+                return 0;
+            }
+            evasions
+        } else {
+            if chess.half_move >= 100 {
+                self.evaluated += 1;
+                return 0;
+            }
+            // a stalemate is left to the main search, the stand pat stands for it here
+            let mut captures = chess.generate_moves(GenType::Captures);
+            // captures that lose material are not worth searching, the promotions are kept
+            captures.retain(|&r#move| !chess.is_capture(r#move) || chess.see_ge(r#move, 0));
+            captures
+        };
         self.order_moves(chess, &mut capture_moves);
-        
-        let mut eval = self.evaluate(chess);
-        if eval >= beta {return beta}
-        alpha = alpha.max(eval);
+
+        let mut eval;
+        if !in_check {
+            eval = self.evaluate(chess);
+            if eval >= beta {return beta}
+            alpha = alpha.max(eval);
+        }
 
         for r#move in capture_moves {
             chess.make_move(r#move);
@@ -190,27 +215,34 @@ impl BossPlayer {
         alpha
     }
 
-    pub fn order_moves(&mut self, chess: &mut Chess, moves: &mut Vec<Move>) {
-        let best_move = self.transposition_table.get_entry(chess.hash()/*, &chess.board*/).map_or(NONE_MOVE, |e|e.r#move);
-        moves.sort_by_cached_key(|&r#move| {
-            if r#move == best_move {return -Eval::MAX}
-            
-            let mut move_score_guess = 0;
-            let move_piece_type = chess.board(r#move.start()).get_type();
-            let capture_piece_type = chess.board(r#move.target()).get_type();
-        
-            if capture_piece_type != NONE_TYPE {
-                move_score_guess = 10 * capture_piece_type.get_piece_value() - move_piece_type.get_piece_value();
-            }
+    pub fn guess_move_score(chess: &Chess, r#move: Move) -> Eval {
+        debug_assert!(chess.has_attack_maps());
+        let mut move_score_guess = 0;
+        let move_piece_type = chess.board(r#move.start()).get_type();
+        let capture_piece_type = chess.board(r#move.target()).get_type();
+    
+        if capture_piece_type != NONE_TYPE && r#move.flag() != CASTLE_FLAG {
+            move_score_guess = 10 * capture_piece_type.get_piece_value() - move_piece_type.get_piece_value();
+        }
 
-            if r#move.is_promotion() {
-                move_score_guess += r#move.promotion_type().get_piece_value();
-            }
+        if r#move.is_promotion() {
+            move_score_guess += r#move.promotion_type().get_piece_value();
+        }
 
-            if chess.side[chess.opponent_index()].piece_attacks[PAWN.piece_index()][r#move.target() as usize] > 0 {
-                move_score_guess -= move_piece_type.get_piece_value();
-            }
-            -move_score_guess
+        // the captures are judged by SEE in the move picker and the quiescence search
+        if !chess.is_capture(r#move)
+        && chess.side[chess.opponent_index()].piece_attacks[PAWN.piece_index()][r#move.target() as usize] > 0 {
+            move_score_guess -= move_piece_type.get_piece_value();
+        }
+        move_score_guess
+    }
+
+    pub fn order_moves(&mut self, chess: &mut Chess, moves: &mut MoveList) {
+        let best_move = self.transposition_table.get_entry(chess.hash()/*, &chess.board*/).map_or(NONE_MOVE, |e|e.r#move);
+        moves.score_moves(|r#move| {
+            if r#move == best_move {return Eval::MAX as i32}
+            BossPlayer::guess_move_score(chess, r#move) as i32
         });
+        moves.sort_by_score();
     }
 }
\ No newline at end of file